@@ -0,0 +1,22 @@
+// `tr!` is expanded away before extraction, its keys are taken from the sources by `extract-macros = ["tr:1"]`
+macro_rules! tr {
+    ($widget:expr, $key:expr) => {
+        format!("{}: {}", $widget, $key)
+    };
+}
+
+pub fn save_label() -> String {
+    tr!("button", "views.save")
+}
+
+pub fn label(key: &str) -> String {
+    tr!("label", key)
+}
+
+pub fn caption(widget: &str, key: &str) -> String {
+    format!("{}: {}", widget, key)
+}
+
+pub fn captions(key: &str) -> [String; 2] {
+    [caption("title", "views.title"), caption("label", key)]
+}
@@ -23,11 +23,61 @@ and support mutiple YAML files merging."##);
             rust_i18n::locale().as_str(),
             #[doc = "TESTTT"]
             "Unfolded.test1.test",
-        )
+        );
+
+        let days = t_list!("days");
+        //expanded/inlined macro t_list!()
+        crate::_rust_i18n_translate_list(
+            rust_i18n::locale().as_str(),
+            "steps",
+        );
+
+        t!("Open", context = "menu");
+        t!("Open", locale = "en", context = "state", name = "file");
+        //expanded/inlined macro t!() with context
+        crate::_rust_i18n_translate_with_context(
+            rust_i18n::locale().as_str(),
+            #[doc = "Open a file"]
+            "Open",
+            "file",
+        );
+        //expanded/inlined macro t!() with variables
+        crate::_rust_i18n_translate_select(
+            rust_i18n::locale().as_str(),
+            "invite",
+            &[("gender", format!("{}", "female"))],
+        );
+        t!(key);
+        t_list!(format!("status.{}", status), locale = "en");
+        rust_i18n::keep_keys!("status.active", "status.disabled");
+        keep_keys!(prefix = "status.");
+        //expanded/inlined macro keep_keys!()
+        const _: () = {
+            let _rust_i18n_keep_keys = ["status.active"];
+            let _rust_i18n_keep_prefix = "status.";
+        };
+        tr!(label, "custom.macro");
+        label("custom.function");
+        macro_rules! wrap {
+            ($key:expr) => { t!($key) };
+        }
+        t!(#[doc = "Download failed"] "error.download.desc");
+        t!(#[doc = "Retry"] "error.retry", context = "button");
+        //expanded/inlined macro t!() with context and variables
+        crate::_rust_i18n_translate_select_with_context(
+            rust_i18n::locale().as_str(),
+            "invite",
+            "formal",
+            args,
+        );
     }
 
     //check if fn definition is not detected
     pub fn _rust_i18n_translate(locale: &str, key: &str) -> String {
         unimplemented!()
     }
+
+    pub fn label(key: &str) -> String {
+        unimplemented!()
+    }
 }
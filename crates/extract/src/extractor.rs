@@ -2,7 +2,11 @@ use anyhow::Error;
 use indexmap::IndexMap;
 use proc_macro2::{Delimiter, Literal, TokenStream, TokenTree};
 use quote::ToTokens;
-use std::path::PathBuf;
+use rust_i18n_support::config::I18nConfig;
+use rust_i18n_support::{context_key, CONTEXT_SEPARATOR};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 pub type Results = IndexMap<String, Message>;
 
@@ -10,6 +14,8 @@ pub type Results = IndexMap<String, Message>;
 pub struct Location {
     pub file: std::path::PathBuf,
     pub line: usize,
+    /// Column of the key literal, in characters from the start of the line
+    pub column: usize,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -18,6 +24,20 @@ pub struct Message {
     pub val: Option<String>,
     pub index: usize,
     pub locations: Vec<Location>,
+    /// Whether the message is a list of `t_list!` (items `key.0`, `key.1`, ...)
+    pub list: bool,
+    /// Context of `t!("Open", context = "menu")`, the key of such message is `Open|menu`
+    pub context: Option<String>,
+    /// Whether all keys starting with the key are kept, by `keep_keys!(prefix = "...")`
+    pub prefix: bool,
+}
+
+/// A `t!` call with a non-literal key, that can not be extracted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicKey {
+    pub location: Location,
+    /// The key expression, e.g. `format!("status.{}", status)`
+    pub expr: String,
 }
 
 impl Message {
@@ -27,34 +47,213 @@ impl Message {
             val,
             index,
             locations: vec![],
+            list: false,
+            context: None,
+            prefix: false,
+        }
+    }
+
+    /// The key without context
+    pub fn source_key(&self) -> &str {
+        match &self.context {
+            Some(context) => self
+                .key
+                .strip_suffix(context.as_str())
+                .and_then(|key| key.strip_suffix(CONTEXT_SEPARATOR))
+                .unwrap_or(&self.key),
+            None => &self.key,
         }
     }
 }
 
+/// A macro or function with a key argument, parsed from `name` or `name:position`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyArg {
+    pub name: String,
+    /// Position of the key in the arguments, starting at 0
+    pub position: usize,
+}
+
+impl FromStr for KeyArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, position) = match s.split_once(':') {
+            Some((name, position)) => {
+                let position = position.parse().map_err(|_| {
+                    format!(
+                        "Invalid position `{}` of `{}`, expected a number",
+                        position, s
+                    )
+                })?;
+                (name, position)
+            }
+            None => (s, 0),
+        };
+        let name = name.trim_end_matches('!');
+        if syn::parse_str::<syn::Ident>(name).is_err() {
+            return Err(format!(
+                "Invalid name `{}`, expected a macro or function name like `tr` or `tr:1`",
+                s
+            ));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            position,
+        })
+    }
+}
+
+/// Macros and functions with a key argument, that are extracted besides `t!` and `t_list!`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractOptions {
+    pub macros: Vec<KeyArg>,
+    pub functions: Vec<KeyArg>,
+}
+
+impl ExtractOptions {
+    /// Options from `extract-macros` and `extract-functions` of the I18n config
+    pub fn from_config(cfg: &I18nConfig) -> Result<Self, String> {
+        let parse = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| name.parse())
+                .collect::<Result<_, _>>()
+        };
+        Ok(Self {
+            macros: parse(&cfg.extract_macros)?,
+            functions: parse(&cfg.extract_functions)?,
+        })
+    }
+}
+
 static METHOD_NAME_MACRO: &str = "t";
 static METHOD_NAME_FN: &str = "_rust_i18n_translate";
-
+static METHOD_NAME_LIST_MACRO: &str = "t_list";
+static METHOD_NAME_LIST_FN: &str = "_rust_i18n_translate_list";
+static METHOD_NAME_SELECT_FN: &str = "_rust_i18n_translate_select";
+static METHOD_NAME_CONTEXT_FN: &str = "_rust_i18n_translate_with_context";
+static METHOD_NAME_SELECT_CONTEXT_FN: &str = "_rust_i18n_translate_select_with_context";
+static CONTEXT_ARG_NAME: &str = "context";
+static KEEP_KEYS_MACRO: &str = "keep_keys";
+static KEEP_KEYS_VAR: &str = "_rust_i18n_keep_keys";
+static KEEP_PREFIX_VAR: &str = "_rust_i18n_keep_prefix";
+static PREFIX_ARG_NAME: &str = "prefix";
+
+/// Extract messages of the expanded source into `results`, return the `t!` calls with non-literal keys
 #[allow(clippy::ptr_arg)]
-pub fn extract(results: &mut Results, path: &PathBuf, source: &str) -> Result<(), Error> {
-    let mut ex = Extractor { results, path };
+pub fn extract(
+    results: &mut Results,
+    path: &PathBuf,
+    source: &str,
+    options: &ExtractOptions,
+) -> Result<Vec<DynamicKey>, Error> {
+    let mut ex = Extractor {
+        results,
+        path,
+        options,
+        dynamic_keys: vec![],
+        sources: false,
+    };
 
     let file = syn::parse_file(source)
         .unwrap_or_else(|_| panic!("Failed to parse file, file: {}", path.display()));
     let stream = file.into_token_stream();
-    ex.invoke(stream)
+    ex.invoke(stream)?;
+    Ok(ex.dynamic_keys)
+}
+
+/// Extract messages of the macros of `options` in the source, that is not expanded, into `results`,
+/// return the `t!` calls with non-literal keys
+///
+/// Macros of `options` are expanded away before `extract`, so they are only found here.
+#[allow(clippy::ptr_arg)]
+pub fn extract_macros(
+    results: &mut Results,
+    path: &PathBuf,
+    source: &str,
+    options: &ExtractOptions,
+) -> Result<Vec<DynamicKey>, Error> {
+    let mut ex = Extractor {
+        results,
+        path,
+        options,
+        dynamic_keys: vec![],
+        sources: true,
+    };
+
+    let file = syn::parse_file(source)
+        .unwrap_or_else(|_| panic!("Failed to parse file, file: {}", path.display()));
+    ex.invoke(file.into_token_stream())?;
+    Ok(ex.dynamic_keys)
+}
+
+/// Extract messages of the macros of `options` in the Rust files in `dirs` into `results`,
+/// return the `t!` calls with non-literal keys, files that fail to parse are skipped
+///
+/// Macros are not expanded, so the locations point to the sources.
+pub fn extract_sources(
+    results: &mut Results,
+    dirs: &[PathBuf],
+    options: &ExtractOptions,
+) -> Result<Vec<DynamicKey>, Error> {
+    let mut dynamic_keys = vec![];
+    for path in dirs
+        .iter()
+        .map(|dir| source_files(dir))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+    {
+        let source = fs::read_to_string(&path)?;
+        if syn::parse_file(&source).is_ok() {
+            dynamic_keys.extend(extract_macros(results, &path, &source, options)?);
+        }
+    }
+    Ok(dynamic_keys)
+}
+
+/// Rust files in `dir` and its sub directories, except `target` and hidden directories
+pub fn source_files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        if path.is_dir() {
+            if name != "target" && !name.starts_with('.') {
+                files.extend(source_files(&path)?);
+            }
+        } else if name.ends_with(".rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
 }
 
 #[allow(dead_code)]
 struct Extractor<'a> {
     results: &'a mut Results,
     path: &'a PathBuf,
+    options: &'a ExtractOptions,
+    dynamic_keys: Vec<DynamicKey>,
+    /// Whether the source is not expanded, then only keys of the macros of `options` are taken,
+    /// and `t!` is only checked for non-literal keys
+    sources: bool,
 }
 
 impl<'a> Extractor<'a> {
     fn invoke(&mut self, stream: TokenStream) -> Result<(), Error> {
         let mut token_iter = stream.into_iter().peekable();
+        // Whether the previous token is `fn`, so the ident is not a call but a definition
+        let mut after_fn = false;
 
         while let Some(token) = token_iter.next() {
+            let is_fn = matches!(&token, TokenTree::Ident(ident) if ident == "fn");
             match token {
                 TokenTree::Group(group) => self.invoke(group.stream())?,
                 TokenTree::Ident(ident) => {
@@ -66,128 +265,368 @@ impl<'a> Extractor<'a> {
                         }
                     }
 
-                    if ident == METHOD_NAME_MACRO && is_macro {
+                    let list = ident == METHOD_NAME_LIST_MACRO || ident == METHOD_NAME_LIST_FN;
+                    if (ident == METHOD_NAME_MACRO || ident == METHOD_NAME_LIST_MACRO) && is_macro {
+                        if let Some(TokenTree::Group(group)) = token_iter.peek() {
+                            self.take_message_macro(group.stream(), list);
+                        }
+                    } else if self.sources {
+                        // Literal keys of functions are taken after expansion
+                        let options = self.options;
+                        let custom = if is_macro {
+                            &options.macros
+                        } else {
+                            &options.functions
+                        };
+                        if let Some(arg) = custom
+                            .iter()
+                            .find(|arg| !after_fn && ident == arg.name.as_str())
+                        {
+                            if let Some(TokenTree::Group(group)) = token_iter.peek() {
+                                self.take_message_arg(group.stream(), arg.position, is_macro);
+                            }
+                        }
+                    } else if ident == METHOD_NAME_FN
+                        || ident == METHOD_NAME_LIST_FN
+                        || ident == METHOD_NAME_SELECT_FN
+                    {
+                        if let Some(TokenTree::Group(group)) = token_iter.peek() {
+                            self.take_message_fn(group.stream(), list);
+                        }
+                    } else if ident == METHOD_NAME_CONTEXT_FN
+                        || ident == METHOD_NAME_SELECT_CONTEXT_FN
+                    {
                         if let Some(TokenTree::Group(group)) = token_iter.peek() {
-                            self.take_message_macro(group.stream());
+                            self.take_message_context_fn(group.stream());
                         }
-                    } else if ident == METHOD_NAME_FN {
+                    } else if ident == KEEP_KEYS_MACRO && is_macro {
                         if let Some(TokenTree::Group(group)) = token_iter.peek() {
-                            self.take_message_fn(group.stream());
+                            self.take_keep_keys(group.stream());
+                        }
+                    } else if ident == KEEP_KEYS_VAR || ident == KEEP_PREFIX_VAR {
+                        // `let _rust_i18n_keep_keys = [...]` expanded from `keep_keys!`
+                        if matches!(token_iter.next(), Some(TokenTree::Punct(punct)) if punct.as_char() == '=')
+                        {
+                            let stream = match token_iter.peek() {
+                                Some(TokenTree::Group(group)) => group.stream(),
+                                Some(tok) => TokenStream::from(tok.clone()),
+                                None => TokenStream::new(),
+                            };
+                            if ident == KEEP_KEYS_VAR {
+                                self.take_keep_keys(stream);
+                            } else {
+                                self.take_keep_prefix(stream);
+                            }
+                        }
+                    } else if !after_fn && !is_macro {
+                        let options = self.options;
+                        if let Some(arg) = options
+                            .functions
+                            .iter()
+                            .find(|arg| ident == arg.name.as_str())
+                        {
+                            if let Some(TokenTree::Group(group)) = token_iter.peek() {
+                                self.take_message_arg(group.stream(), arg.position, true);
+                            }
                         }
                     }
                 }
                 _ => {}
             }
+            after_fn = is_fn;
         }
 
         Ok(())
     }
 
-    fn take_message_inner(&mut self, lit: Literal, val: Option<String>) {
+    fn take_message_inner(
+        &mut self,
+        lit: Literal,
+        val: Option<String>,
+        list: bool,
+        context: Option<String>,
+    ) {
         if let Some(key) = literal_to_string(&lit) {
-            let message_key = format_message_key(&key);
+            let message_key = match &context {
+                Some(context) => context_key(&format_message_key(&key), context),
+                None => format_message_key(&key),
+            };
 
             let index = self.results.len();
-            let message = self
-                .results
-                .entry(message_key.clone())
-                .or_insert_with(|| Message::new(&message_key, index, val));
-
-            let span = lit.span();
-            let line = span.start().line;
-            if line > 0 {
+            let message = self.results.entry(message_key.clone()).or_insert_with(|| {
+                let mut message = Message::new(&message_key, index, val);
+                message.context = context;
+                message
+            });
+            message.list |= list;
+
+            let start = lit.span().start();
+            if start.line > 0 {
                 message.locations.push(Location {
                     file: self.path.clone(),
-                    line,
+                    line: start.line,
+                    column: start.column,
                 });
             }
         }
     }
 
-    fn take_message_macro(&mut self, stream: TokenStream) {
-        let mut token_iter = stream.into_iter();
-
-        let literal = if let Some(TokenTree::Literal(literal)) = token_iter.next() {
-            literal
-        } else {
+    fn take_message_macro(&mut self, stream: TokenStream, list: bool) {
+        let mut args = split_args(stream).into_iter();
+        let Some(key) = args.next() else {
             return;
         };
-        self.take_message_inner(literal, None);
-    }
-    fn take_message_fn(&mut self, stream: TokenStream) {
-        let mut token_iter = stream.into_iter();
-
-        #[derive(PartialEq)]
-        enum DocCommnetState {
-            None,
-            Hash,
-            Group,
+        // A literal key, with doc comments `#[doc = "..."]` for its default text
+        let Some((literal, comment)) = is_literal_with_doc(&key)
+            .then(|| take_literal_with_doc(key.clone()))
+            .flatten()
+        else {
+            self.take_dynamic_key(&mut key.into_iter());
+            return;
+        };
+        // Literal keys are taken after expansion
+        if self.sources {
+            return;
         }
-        let mut state = DocCommnetState::None;
-        let mut literal = None;
-        let mut comment = None;
+        let mut token_iter = args.flatten().peekable();
+
+        // Find `context = "..."` in the arguments
+        let mut context = None;
         while let Some(tok) = token_iter.next() {
-            match tok {
-                TokenTree::Punct(x) if x.as_char() == '#' && state != DocCommnetState::Hash => {
-                    state = DocCommnetState::Hash
+            if let TokenTree::Ident(ident) = tok {
+                if ident != CONTEXT_ARG_NAME {
+                    continue;
                 }
-                TokenTree::Group(group)
-                    if state == DocCommnetState::Hash
-                        && group.delimiter() == Delimiter::Bracket =>
+                if !matches!(token_iter.next(), Some(TokenTree::Punct(punct)) if punct.as_char() == '=')
                 {
-                    let mut i = group.stream().into_iter();
-                    let mut doc = true;
-                    if let Some(TokenTree::Ident(ident)) = i.next() {
-                        doc &= ident.to_string() == "doc"
-                    } else {
-                        doc = false
-                    };
-                    if let Some(TokenTree::Punct(punct)) = i.next() {
-                        doc &= punct.as_char() == '='
+                    continue;
+                }
+                if let Some(TokenTree::Literal(lit)) = token_iter.peek() {
+                    context = literal_to_string(lit);
+                }
+                break;
+            }
+        }
+
+        self.take_message_inner(literal, comment, list, context);
+    }
+
+    /// The key expression of `t!`, up to the first `,`
+    fn take_dynamic_key(&mut self, token_iter: &mut impl Iterator<Item = TokenTree>) {
+        let tokens: Vec<_> = token_iter
+            .take_while(|tok| !matches!(tok, TokenTree::Punct(punct) if punct.as_char() == ','))
+            .collect();
+        // `$key` of a macro definition, the key is given where the macro is used
+        let Some(first) = tokens.first() else {
+            return;
+        };
+        if matches!(first, TokenTree::Punct(punct) if punct.as_char() == '$') {
+            return;
+        }
+        let start = first.span().start();
+        if start.line == 0 {
+            return;
+        }
+
+        self.dynamic_keys.push(DynamicKey {
+            location: Location {
+                file: self.path.clone(),
+                line: start.line,
+                column: start.column,
+            },
+            expr: TokenStream::from_iter(tokens).to_string(),
+        });
+    }
+
+    /// Key argument at `position` of a macro or function of `ExtractOptions`, a literal key is
+    /// only taken if `take_literal`, otherwise only a non-literal key is reported
+    fn take_message_arg(&mut self, stream: TokenStream, position: usize, take_literal: bool) {
+        let Some(arg) = split_args(stream).into_iter().nth(position) else {
+            return;
+        };
+        match arg.clone().into_iter().last() {
+            Some(TokenTree::Literal(_)) if !take_literal => {}
+            Some(TokenTree::Literal(_)) => {
+                if let Some((literal, comment)) = take_literal_with_doc(arg) {
+                    self.take_message_inner(literal, comment, false, None);
+                }
+            }
+            Some(_) => self.take_dynamic_key(&mut arg.into_iter()),
+            None => {}
+        }
+    }
+
+    /// `keep_keys!("a", "b")`, or `keep_keys!(prefix = "a.")`
+    fn take_keep_keys(&mut self, stream: TokenStream) {
+        let mut token_iter = stream.clone().into_iter();
+        if let Some(TokenTree::Ident(ident)) = token_iter.next() {
+            if ident == PREFIX_ARG_NAME
+                && matches!(token_iter.next(), Some(TokenTree::Punct(punct)) if punct.as_char() == '=')
+            {
+                self.take_keep_prefix(token_iter.collect());
+            }
+            return;
+        }
+
+        for tok in stream {
+            if let TokenTree::Literal(lit) = tok {
+                self.take_message_inner(lit, None, false, None);
+            }
+        }
+    }
+
+    fn take_keep_prefix(&mut self, stream: TokenStream) {
+        let Some(TokenTree::Literal(lit)) = stream.into_iter().next() else {
+            return;
+        };
+        let Some(prefix) = literal_to_string(&lit) else {
+            return;
+        };
+
+        // `*` keeps the message apart from a message with the same key
+        let index = self.results.len();
+        let message = self
+            .results
+            .entry(format!("{}*", prefix))
+            .or_insert_with(|| {
+                let mut message = Message::new(&prefix, index, None);
+                message.prefix = true;
+                message
+            });
+
+        let start = lit.span().start();
+        if start.line > 0 {
+            message.locations.push(Location {
+                file: self.path.clone(),
+                line: start.line,
+                column: start.column,
+            });
+        }
+    }
+
+    fn take_message_fn(&mut self, stream: TokenStream, list: bool) {
+        if let Some((literal, comment)) = take_literal_with_doc(stream) {
+            self.take_message_inner(literal, comment, list, None);
+        }
+    }
+
+    /// `_rust_i18n_translate_with_context(locale, key, context)` or
+    /// `_rust_i18n_translate_select_with_context(locale, key, context, args)`, only a literal context is extracted
+    fn take_message_context_fn(&mut self, stream: TokenStream) {
+        let args = split_args(stream);
+        if args.len() < 3 {
+            return;
+        }
+
+        let context = match args[2].clone().into_iter().collect::<Vec<_>>().as_slice() {
+            [TokenTree::Literal(lit)] => literal_to_string(lit),
+            _ => None,
+        };
+        let Some(context) = context else {
+            return;
+        };
+
+        if let Some((literal, comment)) = take_literal_with_doc(args[1].clone()) {
+            self.take_message_inner(literal, comment, false, Some(context));
+        }
+    }
+}
+
+/// Arguments of a call, separated by `,`
+fn split_args(stream: TokenStream) -> Vec<TokenStream> {
+    let mut args = vec![TokenStream::new()];
+    for tok in stream {
+        match tok {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => args.push(TokenStream::new()),
+            tok => args.last_mut().unwrap().extend([tok]),
+        }
+    }
+    args
+}
+
+/// Whether the stream is a literal, with only attributes (`#[doc = "..."]`) before it
+fn is_literal_with_doc(stream: &TokenStream) -> bool {
+    let tokens: Vec<_> = stream.clone().into_iter().collect();
+    match tokens.split_last() {
+        Some((TokenTree::Literal(_), attrs)) => attrs.chunks(2).all(|attr| {
+            matches!(attr, [TokenTree::Punct(punct), TokenTree::Group(group)]
+                if punct.as_char() == '#' && group.delimiter() == Delimiter::Bracket)
+        }),
+        _ => false,
+    }
+}
+
+/// Take the last literal in stream, with the doc comments (`#[doc = "..."]`) right before it
+fn take_literal_with_doc(stream: TokenStream) -> Option<(Literal, Option<String>)> {
+    let token_iter = stream.into_iter();
+
+    #[derive(PartialEq)]
+    enum DocCommnetState {
+        None,
+        Hash,
+        Group,
+    }
+    let mut state = DocCommnetState::None;
+    let mut literal = None;
+    let mut comment = None;
+    for tok in token_iter {
+        match tok {
+            TokenTree::Punct(x) if x.as_char() == '#' && state != DocCommnetState::Hash => {
+                state = DocCommnetState::Hash
+            }
+            TokenTree::Group(group)
+                if state == DocCommnetState::Hash && group.delimiter() == Delimiter::Bracket =>
+            {
+                let mut i = group.stream().into_iter();
+                let mut doc = true;
+                if let Some(TokenTree::Ident(ident)) = i.next() {
+                    doc &= ident == "doc"
+                } else {
+                    doc = false
+                };
+                if let Some(TokenTree::Punct(punct)) = i.next() {
+                    doc &= punct.as_char() == '='
+                } else {
+                    doc = false
+                };
+                if doc {
+                    if let Some(TokenTree::Literal(lit)) = i.next() {
+                        let lit = lit
+                            .to_string()
+                            .trim()
+                            .trim_start_matches("r\"")
+                            .trim_end_matches('\"')
+                            .trim_start_matches('\"')
+                            .trim()
+                            .to_string();
+                        if let Some(cmt) = comment {
+                            comment = Some(cmt + "\r\n" + lit.as_str())
+                        } else {
+                            comment = Some(lit)
+                        }
                     } else {
                         doc = false
                     };
-                    if doc {
-                        if let Some(TokenTree::Literal(lit)) = i.next() {
-                            let lit = lit
-                                .to_string()
-                                .trim()
-                                .trim_start_matches("r\"")
-                                .trim_end_matches('\"')
-                                .trim_start_matches('\"')
-                                .trim()
-                                .to_string();
-                            if let Some(cmt) = comment {
-                                comment = Some(cmt + "\r\n" + lit.as_str())
-                            } else {
-                                comment = Some(lit)
-                            }
-                        } else {
-                            doc = false
-                        };
-                    }
-
-                    if !doc {
-                        state = DocCommnetState::None
-                    } else {
-                        state = DocCommnetState::Group
-                    }
                 }
-                TokenTree::Literal(lit) => {
-                    if state != DocCommnetState::Group {
-                        comment = None;
-                    }
-                    //take last literal in stream
-                    literal = Some(lit);
+
+                if !doc {
                     state = DocCommnetState::None
+                } else {
+                    state = DocCommnetState::Group
                 }
-                _ => state = DocCommnetState::None,
             }
-        }
-        if let Some(literal) = literal {
-            self.take_message_inner(literal, comment);
+            TokenTree::Literal(lit) => {
+                if state != DocCommnetState::Group {
+                    comment = None;
+                }
+                //take last literal in stream
+                literal = Some(lit);
+                state = DocCommnetState::None
+            }
+            _ => state = DocCommnetState::None,
         }
     }
+    literal.map(|literal| (literal, comment))
 }
 
 fn literal_to_string(lit: &proc_macro2::Literal) -> Option<String> {
@@ -219,11 +658,15 @@ mod tests {
                         $(
                             Location {
                                 file: PathBuf::from_str("hello.rs").unwrap(),
-                                line: $line
+                                line: $line,
+                                column: 0,
                             },
                         )+
                     ],
                     index: 0,
+                    list: false,
+                    context: None,
+                    prefix: false,
                 };
                 results.push(message);
             )+
@@ -266,6 +709,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_source_key() {
+        let mut message = Message::new("Open|menu", 0, None);
+        assert_eq!(message.source_key(), "Open|menu");
+        message.context = Some("menu".to_string());
+        assert_eq!(message.source_key(), "Open");
+    }
+
+    #[test]
+    fn test_key_arg() {
+        let arg: KeyArg = "tr".parse().unwrap();
+        assert_eq!((arg.name.as_str(), arg.position), ("tr", 0));
+        let arg: KeyArg = "label!:2".parse().unwrap();
+        assert_eq!((arg.name.as_str(), arg.position), ("label", 2));
+        assert!("tr:x".parse::<KeyArg>().is_err());
+        assert!("i18n::tr".parse::<KeyArg>().is_err());
+        assert!("".parse::<KeyArg>().is_err());
+    }
+
     #[test]
     fn test_extract() {
         let source = include_str!("example.test.rs");
@@ -287,17 +749,63 @@ mod tests {
                 18,
                 20
             ),
-            ("Unfolded.test1.test", Some("TESTTT".to_string()), 25)
+            ("Unfolded.test1.test", Some("TESTTT".to_string()), 25),
+            ("days", None, 28),
+            ("steps", None, 32),
+            ("Open|menu", None, 35),
+            ("Open|state", None, 36),
+            ("Open|file", Some("Open a file".to_string()), 41),
+            ("invite", None, 47),
+            ("status.active", None, 52, 56),
+            ("status.disabled", None, 52),
+            ("status.", None, 53, 57),
+            ("custom.function", None, 60),
+            (
+                "error.download.desc",
+                Some("Download failed".to_string()),
+                64
+            ),
+            ("error.retry|button", Some("Retry".to_string()), 65),
+            ("invite|formal", None, 69)
         ];
+        let expected = expected
+            .into_iter()
+            .map(|mut m| {
+                m.list = m.key == "days" || m.key == "steps";
+                m.prefix = m.key == "status.";
+                m.context = m
+                    .key
+                    .split_once('|')
+                    .map(|(_, context)| context.to_string());
+                m
+            })
+            .collect::<Vec<_>>();
 
         let mut results = IndexMap::new();
 
+        let options = ExtractOptions {
+            macros: vec!["tr:1".parse().unwrap()],
+            functions: vec!["label".parse().unwrap()],
+        };
         let mut ex = Extractor {
             results: &mut results,
             path: &"hello.rs".to_owned().into(),
+            options: &options,
+            dynamic_keys: vec![],
+            sources: false,
         };
 
-        ex.invoke(stream).unwrap();
+        ex.invoke(stream.clone()).unwrap();
+
+        let dynamic_keys: Vec<_> = ex
+            .dynamic_keys
+            .iter()
+            .map(|k| (k.location.line, k.expr.as_str()))
+            .collect();
+        assert_eq!(
+            dynamic_keys,
+            [(50, "key"), (51, "format ! (\"status.{}\" , status)")]
+        );
 
         let mut messages: Vec<_> = ex.results.values().collect();
         messages.sort_by_key(|m| m.index);
@@ -306,8 +814,32 @@ mod tests {
         for (expected_message, actually_message) in expected.iter().zip(messages) {
             let mut actually_message = actually_message.clone();
             actually_message.index = 0;
+            assert!(actually_message.locations.iter().any(|l| l.column > 0));
+            for location in &mut actually_message.locations {
+                location.column = 0;
+            }
 
             assert_eq!(*expected_message, actually_message);
         }
+
+        // Only keys of macros of the options are taken from sources
+        let mut results = IndexMap::new();
+        let mut ex = Extractor {
+            results: &mut results,
+            path: &"hello.rs".to_owned().into(),
+            options: &options,
+            dynamic_keys: vec![],
+            sources: true,
+        };
+
+        ex.invoke(stream).unwrap();
+
+        assert_eq!(ex.dynamic_keys.len(), 2);
+        let messages: Vec<_> = ex
+            .results
+            .values()
+            .map(|m| (m.key.as_str(), m.locations[0].line))
+            .collect();
+        assert_eq!(messages, [("custom.macro", 59)]);
     }
 }
@@ -1,17 +1,27 @@
 use crate::extractor::Message;
+use crate::rename::{rename_translations, KeyRename};
 use indexmap::IndexMap;
+use indexmap::IndexSet;
 use rust_i18n_support::config::I18nConfig;
-use rust_i18n_support::load_locales;
+use rust_i18n_support::{
+    load_locales_with_meta, load_locales_with_options, locale_files, LocaleFile, LocaleLayout,
+    LABEL_FUZZY, LABEL_STALE,
+};
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 type Translations = IndexMap<String, IndexMap<String, String>>;
+/// Translations of one locale, with lists (`key.0`, `key.1`, ...) as arrays
+type NestedTranslations = IndexMap<String, serde_json::Value>;
+
 /// Translations can be either IndexMap<locale, IndexMap<text_key, text>> or IndexMap<text_key, IndexMap<locale, text>>
 /// this function changes between them
-fn translations_transpose(i: &Translations) -> Translations {
-    let mut out = Translations::new();
+fn translations_transpose<V: Clone>(
+    i: &IndexMap<String, IndexMap<String, V>>,
+) -> IndexMap<String, IndexMap<String, V>> {
+    let mut out = IndexMap::<String, IndexMap<String, V>>::new();
     i.iter().for_each(|(okey, oval)| {
         oval.iter().for_each(|(ikey, ival)| {
             out.entry(ikey.clone())
@@ -22,20 +32,173 @@ fn translations_transpose(i: &Translations) -> Translations {
     out
 }
 
+/// File in the locales directory with the texts of the default locale, that the translations were made for
+pub const SNAPSHOT_FILE_NAME: &str = ".i18n-sources";
+/// Minimum similarity of texts for a fuzzy suggestion
+const FUZZY_THRESHOLD: f64 = 0.75;
+/// Label of entries of TODO files, that are translated and moved to the locale files
+const LABEL_DONE: &str = "DONE";
+
+/// Similarity of texts ignoring case, `1 - edit distance / length of the longer text`
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    1.0 - row[b.len()] as f64 / max_len as f64
+}
+
+/// Translation of the text most similar to `text` in `memory` (pairs of text in the default locale, translation),
+/// if it is similar enough
+fn fuzzy_suggestion<'a>(memory: &'a [(&str, &str)], text: &str) -> Option<&'a str> {
+    let len = text.chars().count() as f64;
+    memory
+        .iter()
+        // Skip texts that differ too much in length to be similar enough
+        .filter(|(source, _)| {
+            let source_len = source.chars().count() as f64;
+            (len - source_len).abs() <= (1.0 - FUZZY_THRESHOLD) * len.max(source_len)
+        })
+        .map(|(source, translation)| (similarity(source, text), *translation))
+        .filter(|(similarity, _)| *similarity >= FUZZY_THRESHOLD)
+        .fold(None, |best: Option<(f64, &str)>, candidate| match best {
+            Some(best) if best.0 >= candidate.0 => Some(best),
+            _ => Some(candidate),
+        })
+        .map(|(_, translation)| translation)
+}
+
+/// Convert lists, keys `key.0`, `key.1`, ... (without gaps and other keys starting with `key.`), into arrays.
+/// Items with keys of their own, e.g. `steps.0.title`, are converted into maps, with their lists converted too.
+fn nest_lists(trs: &IndexMap<String, String>) -> NestedTranslations {
+    // Indexes of lists by their key, for every numeric segment of keys
+    let mut lists = IndexMap::<String, IndexSet<usize>>::new();
+    for key in trs.keys() {
+        let segments = key.split('.').collect::<Vec<_>>();
+        for (i, segment) in segments.iter().enumerate().skip(1) {
+            if let Ok(index) = segment.parse::<usize>() {
+                lists
+                    .entry(segments[..i].join("."))
+                    .or_default()
+                    .insert(index);
+            }
+        }
+    }
+    lists.retain(|base, indexes| {
+        let prefix = format!("{base}.");
+        let item_keys = |i: usize| {
+            let item = format!("{prefix}{i}");
+            let item_prefix = format!("{item}.");
+            (
+                trs.contains_key(&item),
+                trs.keys().any(|k| k.starts_with(&item_prefix)),
+            )
+        };
+        !trs.contains_key(base)
+            && (0..indexes.len()).all(|i| indexes.contains(&i))
+            && trs
+                .keys()
+                .filter_map(|k| k.strip_prefix(&prefix))
+                .all(|rest| rest.split('.').next().unwrap_or_default().parse::<usize>().is_ok())
+            // An item is either a text or a map
+            && (0..indexes.len()).all(|i| !matches!(item_keys(i), (true, true)))
+    });
+    // Lists in items of other lists are converted with their items
+    let outer = lists
+        .keys()
+        .filter(|base| {
+            !lists
+                .keys()
+                .any(|other| base.starts_with(&format!("{other}.")))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut result = NestedTranslations::new();
+    for (key, value) in trs {
+        let list = outer
+            .iter()
+            .find(|base| key.starts_with(&format!("{base}.")));
+        match list {
+            Some(base) => {
+                if !result.contains_key(base) {
+                    let items = (0..lists[base].len())
+                        .map(|i| list_item(trs, &format!("{base}.{i}")))
+                        .collect();
+                    result.insert(base.clone(), serde_json::Value::Array(items));
+                }
+            }
+            None => {
+                result.insert(key.clone(), serde_json::Value::String(value.clone()));
+            }
+        }
+    }
+    result
+}
+
+/// Item of a list, the text of the key or a map of the keys starting with it
+fn list_item(trs: &IndexMap<String, String>, key: &str) -> serde_json::Value {
+    if let Some(text) = trs.get(key) {
+        return serde_json::Value::String(text.clone());
+    }
+
+    let prefix = format!("{key}.");
+    let item = trs
+        .iter()
+        .filter_map(|(k, v)| Some((k.strip_prefix(&prefix)?.to_string(), v.clone())))
+        .collect::<IndexMap<_, _>>();
+    serde_json::Value::Object(nest_lists(&item).into_iter().collect())
+}
+
+/// Keys of variants `key.%{arg}.variant` of a select map
+fn variant_keys(trs: &IndexMap<String, String>, key: &str) -> Vec<String> {
+    let prefix = format!("{key}.%{{");
+    trs.keys()
+        .filter(|k| k.starts_with(&prefix))
+        .cloned()
+        .collect()
+}
+
+/// Keys of list items `key.0`, `key.1`, ... in order
+fn list_item_keys(trs: &IndexMap<String, String>, key: &str) -> Vec<String> {
+    (0..)
+        .map(|i| format!("{key}.{i}"))
+        .take_while(|k| trs.contains_key(k))
+        .collect()
+}
+
 #[derive(Serialize, Deserialize)]
 struct FileVer1 {
     _version: usize,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    _meta: IndexMap<String, String>,
     #[serde(flatten)]
     // #[serde(with = "indexmap::map::serde_seq")]
-    translations: IndexMap<String, String>,
+    translations: NestedTranslations,
 }
 
 #[derive(Serialize, Deserialize)]
 struct FileVer2 {
     _version: usize,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    _meta: Translations,
     #[serde(flatten)]
     // #[serde(with = "indexmap::map::serde_seq")]
-    translations: Translations,
+    translations: IndexMap<String, NestedTranslations>,
 }
 
 pub fn generate<'a, P: AsRef<Path>>(
@@ -49,9 +212,10 @@ pub fn generate<'a, P: AsRef<Path>>(
     let ignore_file_ndone = |fname: &str| fname.contains("TODO") || fname.contains("REMOVED");
     let ignore_file_ntodo = |fname: &str| !fname.contains("TODO");
     let ignore_file_nremoved = |fname: &str| !fname.contains("REMOVED");
-    let mut data_done = load_locales(&output_path, ignore_file_ndone);
-    let mut data_todo = load_locales(&output_path, ignore_file_ntodo);
-    let mut data_removed = load_locales(&output_path, ignore_file_nremoved);
+    let options = cfg.load_options();
+    let (mut data_done, meta) = load_locales_with_meta(&output_path, ignore_file_ndone, &options);
+    let mut data_todo = load_locales_with_options(&output_path, ignore_file_ntodo, &options);
+    let mut data_removed = load_locales_with_options(&output_path, ignore_file_nremoved, &options);
 
     update_todo_done_removed(
         &mut data_done,
@@ -63,9 +227,22 @@ pub fn generate<'a, P: AsRef<Path>>(
         true,
     );
     let data_done_default = data_done.get(&cfg.default_locale).unwrap().clone();
+    let snapshot_path = output.as_ref().join(SNAPSHOT_FILE_NAME);
+    let mut snapshot = load_snapshot(&snapshot_path);
+
     let mut all_locales = cfg.available_locales.clone();
     all_locales.retain(|locale| locale != &cfg.default_locale);
     for locale in all_locales {
+        let confirmed: Vec<String> = data_todo
+            .get(&locale)
+            .map(|list_todo| {
+                list_todo
+                    .iter()
+                    .filter(|(_, value)| value.starts_with(LABEL_DONE))
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
         update_todo_done_removed(
             &mut data_done,
             &mut data_todo,
@@ -75,19 +252,45 @@ pub fn generate<'a, P: AsRef<Path>>(
             messages.clone(),
             false,
         );
+        let list_done = data_done.get_mut(&locale).unwrap();
+        let sources = snapshot.entry(locale.clone()).or_default();
+        confirm_sources(sources, list_done, &confirmed, &data_done_default);
+        let list_done = &data_done[&locale];
+        let stale = update_sources(sources, list_done, &data_done_default);
+        for (key, (old, new)) in &stale {
+            println!(
+                "Text of `{}` changed, its [{}] translation is added to TODO:\n  old: {}\n  new: {}",
+                key, locale, old, new
+            );
+        }
+        let list_todo = data_todo.entry(locale.clone()).or_default();
+        mark_stale(list_todo, list_done, &stale);
     }
+    write_snapshot(&snapshot_path, &snapshot);
 
-    write_file(&output, "", &cfg, &data_done, &|_, _| ());
-    write_file(&output, "TODO.", &cfg, &data_todo, &|count, filename| {
-        eprintln!("Found {} new texts need to translate.", count);
-        eprintln!("----------------------------------------");
-        eprintln!("Writing to {}\n", filename);
-    });
+    write_file(&output, "", cfg, &data_done, &meta, false, &|_, _| ());
+    let no_meta = Translations::new();
+    // TODO files are generated again each time, without texts left to translate they are outdated
+    write_file(
+        &output,
+        "TODO.",
+        cfg,
+        &data_todo,
+        &no_meta,
+        true,
+        &|count, filename| {
+            eprintln!("Found {} new texts need to translate.", count);
+            eprintln!("----------------------------------------");
+            eprintln!("Writing to {}\n", filename);
+        },
+    );
     write_file(
         &output,
         "REMOVED.",
-        &cfg,
+        cfg,
         &data_removed,
+        &no_meta,
+        false,
         &|count, filename| {
             eprintln!("Found {} unused texts to remove.", count);
             eprintln!("----------------------------------------");
@@ -96,6 +299,189 @@ pub fn generate<'a, P: AsRef<Path>>(
     );
 }
 
+/// Rename keys in the DONE, TODO and REMOVED files of all locales, return the number of renamed translations
+pub fn rename<P: AsRef<Path>>(
+    output: P,
+    cfg: &I18nConfig,
+    rename: &KeyRename,
+) -> Result<usize, String> {
+    let output_path = output.as_ref().display().to_string();
+
+    let ignore_file_ndone = |fname: &str| fname.contains("TODO") || fname.contains("REMOVED");
+    let ignore_file_ntodo = |fname: &str| !fname.contains("TODO");
+    let ignore_file_nremoved = |fname: &str| !fname.contains("REMOVED");
+    let options = cfg.load_options();
+    let (mut data_done, meta) = load_locales_with_meta(&output_path, ignore_file_ndone, &options);
+    let mut data_todo = load_locales_with_options(&output_path, ignore_file_ntodo, &options);
+    let mut data_removed = load_locales_with_options(&output_path, ignore_file_nremoved, &options);
+
+    // Check all files before changing any of them
+    let mut renamed = [data_done.clone(), data_todo.clone(), data_removed.clone()];
+    for trs in &mut renamed {
+        rename_translations(trs, rename)?;
+    }
+
+    let mut count = 0;
+    count += rename_translations(&mut data_done, rename)?;
+    count += rename_translations(&mut data_todo, rename)?;
+    count += rename_translations(&mut data_removed, rename)?;
+    if count == 0 {
+        return Ok(0);
+    }
+
+    // Write all loaded locales, not only the available ones, and remove files left without translations
+    let mut cfg = cfg.clone();
+    for locale in data_done
+        .keys()
+        .chain(data_todo.keys())
+        .chain(data_removed.keys())
+    {
+        if !cfg.available_locales.contains(locale) {
+            cfg.available_locales.push(locale.clone());
+        }
+    }
+    let cfg = &cfg;
+    let no_meta = Translations::new();
+    write_file(&output, "", cfg, &data_done, &meta, true, &|_, _| ());
+    write_file(
+        &output,
+        "TODO.",
+        cfg,
+        &data_todo,
+        &no_meta,
+        true,
+        &|_, _| (),
+    );
+    write_file(
+        &output,
+        "REMOVED.",
+        cfg,
+        &data_removed,
+        &no_meta,
+        true,
+        &|_, _| (),
+    );
+
+    let snapshot_path = output.as_ref().join(SNAPSHOT_FILE_NAME);
+    if snapshot_path.exists() {
+        let mut snapshot = load_snapshot(&snapshot_path);
+        for sources in snapshot.values_mut() {
+            *sources = sources
+                .drain(..)
+                .map(|(key, source)| (rename.rename(&key).unwrap_or(key), source))
+                .collect();
+        }
+        write_snapshot(&snapshot_path, &snapshot);
+    }
+
+    Ok(count)
+}
+
+/// A translation and the text of the default locale it was made for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Source {
+    source: String,
+    translation: String,
+}
+
+/// Sources of the translations by locale and key, e.g. `{"fr": {"hello": {"source": "Hello", "translation": "Bonjour"}}}`
+type Snapshot = IndexMap<String, IndexMap<String, Source>>;
+
+/// Sources of the translations, empty if there is no snapshot yet
+fn load_snapshot(path: &Path) -> Snapshot {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_yaml::from_str(&content)
+            .unwrap_or_else(|e| panic!("Invalid snapshot {}, {}", path.display(), e)),
+        Err(_) => Snapshot::new(),
+    }
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) {
+    let mut snapshot = snapshot.clone();
+    snapshot.retain(|_, sources| !sources.is_empty());
+    snapshot.sort_unstable_keys();
+    for sources in snapshot.values_mut() {
+        sources.sort_unstable_keys();
+    }
+    let (content, _) = format_data(&snapshot, "yaml");
+    std::fs::write(path, content)
+        .unwrap_or_else(|_| panic!("Unable to write {} file", path.display()));
+}
+
+/// Accept translations confirmed in TODO files (marked `DONE`), also if they did not change: record their
+/// current text of the default locale, and remove the old and the new text of a `STALE` mark.
+fn confirm_sources(
+    sources: &mut IndexMap<String, Source>,
+    list_done: &mut IndexMap<String, String>,
+    confirmed: &[String],
+    default_val: &IndexMap<String, String>,
+) {
+    for key in confirmed {
+        let (Some(text), Some(translation)) = (default_val.get(key), list_done.get_mut(key)) else {
+            continue;
+        };
+        if let Some(source) = sources.get(key) {
+            let note = format!(" [old: {}, new: {}]", source.source, text);
+            if let Some(stripped) = translation.strip_suffix(&note) {
+                *translation = stripped.to_string();
+            }
+        }
+        let source = Source {
+            source: text.clone(),
+            translation: translation.clone(),
+        };
+        sources.insert(key.clone(), source);
+    }
+}
+
+/// Record the text of the default locale for translations of a locale, that are new or changed.
+/// Return the stale translations, that did not change while their text of the default locale did,
+/// with the old and the new text.
+fn update_sources(
+    sources: &mut IndexMap<String, Source>,
+    list_done: &IndexMap<String, String>,
+    default_val: &IndexMap<String, String>,
+) -> IndexMap<String, (String, String)> {
+    sources.retain(|key, _| list_done.contains_key(key) && default_val.contains_key(key));
+
+    let mut stale = IndexMap::new();
+    for (key, translation) in list_done {
+        let Some(text) = default_val.get(key) else {
+            continue;
+        };
+        match sources.get(key) {
+            Some(source) if &source.translation == translation && &source.source != text => {
+                stale.insert(key.clone(), (source.source.clone(), text.clone()));
+            }
+            _ => {
+                let source = Source {
+                    source: text.clone(),
+                    translation: translation.clone(),
+                };
+                sources.insert(key.clone(), source);
+            }
+        }
+    }
+    stale
+}
+
+/// Add stale translations to TODO, marked as stale and with the old and the new text of the default locale.
+/// They are kept in the locale files and stay in use until they are translated again, as an outdated
+/// translation is closer to the text than the text of the fallback locale.
+fn mark_stale(
+    list_todo: &mut IndexMap<String, String>,
+    list_done: &IndexMap<String, String>,
+    stale: &IndexMap<String, (String, String)>,
+) {
+    for (key, (old, new)) in stale {
+        let value = format!(
+            "{} {} [old: {}, new: {}]",
+            LABEL_STALE, list_done[key], old, new
+        );
+        list_todo.insert(key.clone(), value);
+    }
+}
+
 fn update_todo_done_removed<'a>(
     data_done: &mut Translations,
     data_todo: &mut Translations,
@@ -110,9 +496,8 @@ fn update_todo_done_removed<'a>(
     let list_todo = data_todo.entry(locale.clone()).or_default();
     let list_removed = data_removed.entry(locale.clone()).or_default();
 
-    let label_todo_done = "DONE";
     list_todo.into_iter().for_each(|(key, value)| {
-        if let Some(stripped) = value.strip_prefix(label_todo_done) {
+        if let Some(stripped) = value.strip_prefix(LABEL_DONE) {
             //"TODO.*.yml" file entry, but marked with @DONE@, so move it to "Done" file and strip this prefix
             list_done.insert(key.clone(), stripped.trim_start().to_string());
         }
@@ -120,6 +505,20 @@ fn update_todo_done_removed<'a>(
     list_todo.clear();
     let mut list_done_to_removed = list_done.clone();
     list_done.clear();
+
+    // Translations of texts of the default locale, for suggestions of new texts
+    let done = list_done_to_removed.clone();
+    let memory: Vec<(&str, &str)> = if default_locale {
+        vec![]
+    } else {
+        done.iter()
+            .filter_map(|(key, translation)| {
+                Some((default_val.get(key)?.as_str(), translation.as_str()))
+            })
+            .filter(|(source, translation)| !source.is_empty() && !translation.is_empty())
+            .collect()
+    };
+    let mut fuzzy_count = 0;
     // TODO.en.yml
     for m in messages {
         if !m.locations.is_empty() {
@@ -128,6 +527,36 @@ fn update_todo_done_removed<'a>(
             }
         }
 
+        if m.prefix {
+            update_prefix(
+                list_done,
+                list_todo,
+                &mut list_done_to_removed,
+                default_val,
+                m,
+            );
+            continue;
+        }
+
+        if m.list {
+            update_list(
+                list_done,
+                list_todo,
+                &mut list_done_to_removed,
+                default_val,
+                m,
+            );
+            continue;
+        }
+
+        update_variants(
+            list_done,
+            list_todo,
+            &mut list_done_to_removed,
+            default_val,
+            m,
+        );
+
         if list_done_to_removed.contains_key(&m.key) {
             list_done.insert(
                 m.key.clone(),
@@ -149,8 +578,8 @@ fn update_todo_done_removed<'a>(
             }
             let mut trailing_dots = 0;
             let mut val = "".to_string();
-            for i in m.key.split('.').rev() {
-                if i == "" {
+            for i in m.source_key().split('.').rev() {
+                if i.is_empty() {
                     trailing_dots += 1;
                 } else {
                     val = i.to_string();
@@ -163,50 +592,361 @@ fn update_todo_done_removed<'a>(
             val
         };
 
+        let value = match fuzzy_suggestion(&memory, &value) {
+            Some(suggestion) => {
+                fuzzy_count += 1;
+                format!("{} {}", LABEL_FUZZY, suggestion)
+            }
+            None => value,
+        };
         list_todo.insert(m.key.clone(), value);
     }
 
+    if fuzzy_count > 0 {
+        println!(
+            "Prefilled {} untranslated texts with translations of similar texts, marked with {}",
+            fuzzy_count, LABEL_FUZZY
+        );
+    }
+
     //move entries from DONE, that has not not be found in newly extracted ones, to REMOVED file
     list_removed.extend(list_done_to_removed.drain(..));
 }
 
+/// Keep the variants of a select map of the message, and add the variants of the default locale that are missing to TODO
+fn update_variants(
+    list_done: &mut IndexMap<String, String>,
+    list_todo: &mut IndexMap<String, String>,
+    list_done_to_removed: &mut IndexMap<String, String>,
+    default_val: &IndexMap<String, String>,
+    m: &Message,
+) {
+    for key in variant_keys(list_done_to_removed, &m.key) {
+        let value = list_done_to_removed.swap_remove(&key).unwrap();
+        list_done.insert(key, value);
+    }
+
+    for key in variant_keys(default_val, &m.key) {
+        if !list_done.contains_key(&key) {
+            list_todo.insert(key.clone(), default_val[&key].clone());
+        }
+    }
+}
+
+/// Keep all keys starting with the prefix of `keep_keys!(prefix = "...")`,
+/// and add the keys of the default locale with the prefix that are missing to TODO
+fn update_prefix(
+    list_done: &mut IndexMap<String, String>,
+    list_todo: &mut IndexMap<String, String>,
+    list_done_to_removed: &mut IndexMap<String, String>,
+    default_val: &IndexMap<String, String>,
+    m: &Message,
+) {
+    let keys: Vec<_> = list_done_to_removed
+        .keys()
+        .filter(|key| key.starts_with(&m.key))
+        .cloned()
+        .collect();
+    for key in keys {
+        let value = list_done_to_removed.swap_remove(&key).unwrap();
+        list_done.insert(key, value);
+    }
+
+    for (key, value) in default_val {
+        if key.starts_with(&m.key) && !list_done.contains_key(key) {
+            list_todo.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// Same as `update_todo_done_removed` for a message of `t_list!`, that keeps all items `key.0`, `key.1`, ...
+fn update_list(
+    list_done: &mut IndexMap<String, String>,
+    list_todo: &mut IndexMap<String, String>,
+    list_done_to_removed: &mut IndexMap<String, String>,
+    default_val: &IndexMap<String, String>,
+    m: &Message,
+) {
+    let items = list_item_keys(list_done_to_removed, &m.key);
+    if !items.is_empty() {
+        for key in items {
+            let value = list_done_to_removed.swap_remove(&key).unwrap();
+            list_done.insert(key, value);
+        }
+        return;
+    }
+
+    let items = list_item_keys(default_val, &m.key);
+    if !items.is_empty() {
+        for key in items {
+            let value = default_val[&key].clone();
+            list_todo.insert(key, value);
+        }
+        return;
+    }
+
+    let value = m.key.rsplit('.').next().unwrap_or_default().to_string();
+    list_todo.insert(format!("{}.0", m.key), value);
+}
+
+/// Write translations, `meta` is the `_meta` section of each locale, written into the files without namespace.
+/// Files without translations are skipped, or removed with `remove_empty`, so they do not keep outdated ones.
 fn write_file<P: AsRef<Path>>(
     output: &P,
     filename_prefix: &str,
     cfg: &I18nConfig,
     translations: &Translations,
+    meta: &Translations,
+    remove_empty: bool,
+    msg: &dyn Fn(usize, &str),
+) {
+    let files = locale_files(&output.as_ref().display().to_string(), &cfg.load_options());
+    if !cfg.namespaced {
+        write_namespace_file(
+            output,
+            &files,
+            filename_prefix,
+            "",
+            cfg,
+            translations,
+            meta,
+            remove_empty,
+            msg,
+        );
+        return;
+    }
+
+    let namespaces = files
+        .iter()
+        .map(|file| file.namespace.clone())
+        .collect::<IndexSet<_>>();
+    let mut translations = split_namespaces(translations, &namespaces);
+    if !meta.is_empty() {
+        translations.entry(String::new()).or_default();
+    }
+    if remove_empty {
+        for namespace in namespaces {
+            translations.entry(namespace).or_default();
+        }
+    }
+    for (namespace, translations) in translations {
+        let no_meta = Translations::new();
+        let meta = if namespace.is_empty() { meta } else { &no_meta };
+        write_namespace_file(
+            output,
+            &files,
+            filename_prefix,
+            &namespace,
+            cfg,
+            &translations,
+            meta,
+            remove_empty,
+            msg,
+        );
+    }
+}
+
+/// Split translations by the longest namespace (of existing files) their keys start with,
+/// and strip that namespace from keys.
+fn split_namespaces(
+    translations: &Translations,
+    namespaces: &IndexSet<String>,
+) -> IndexMap<String, Translations> {
+    let mut result = IndexMap::<String, Translations>::new();
+
+    for (locale, trs) in translations {
+        for (key, value) in trs {
+            let namespace = namespaces
+                .iter()
+                .filter(|ns| !ns.is_empty() && key.starts_with(&format!("{ns}.")))
+                .max_by_key(|ns| ns.len());
+            let (namespace, key) = match namespace {
+                Some(ns) => (ns.clone(), key[ns.len() + 1..].to_string()),
+                None => (String::new(), key.clone()),
+            };
+            result
+                .entry(namespace)
+                .or_default()
+                .entry(locale.clone())
+                .or_default()
+                .insert(key, value.clone());
+        }
+    }
+
+    // every namespace has all locales, same as not splitted translations
+    for trs in result.values_mut() {
+        for locale in translations.keys() {
+            trs.entry(locale.clone()).or_default();
+        }
+    }
+
+    result
+}
+
+/// File name without prefix and extension, e.g. `view.en`
+fn namespaced_name(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{namespace}.{name}")
+    }
+}
+
+/// Path of the file with the translations of a namespace, relative to the locales directory and
+/// without extension. That is the file they were loaded from, else the same file of another locale,
+/// e.g. `fr/admin/users` for `en/admin/users.yml`, else a new file named after the namespace.
+/// `locale` is `None` for files of version 2, which contain all locales.
+fn file_path(
+    files: &[LocaleFile],
+    cfg: &I18nConfig,
+    namespace: &str,
+    locale: Option<&str>,
+) -> PathBuf {
+    let candidates = |locale: Option<&str>| {
+        files
+            .iter()
+            .filter(|file| {
+                let name = file.path.file_name().and_then(|s| s.to_str());
+                let name = name.unwrap_or_default();
+                !name.starts_with("TODO.") && !name.starts_with("REMOVED.")
+            })
+            .filter(|file| !cfg.namespaced || file.namespace == namespace)
+            .filter(|file| match locale {
+                Some(locale) => file.locale == locale,
+                None => !cfg.available_locales.contains(&file.locale),
+            })
+            .map(|file| file.path.with_extension(""))
+            .collect::<IndexSet<_>>()
+    };
+
+    let Some(locale) = locale else {
+        let candidates = candidates(None);
+        return match candidates.len() {
+            1 => candidates[0].clone(),
+            _ => PathBuf::from(namespaced_name(namespace, &cfg.generate_file_name)),
+        };
+    };
+
+    let candidates_of_locale = candidates(Some(locale));
+    if candidates_of_locale.len() == 1 {
+        return candidates_of_locale[0].clone();
+    }
+    if candidates_of_locale.is_empty() {
+        let others = std::iter::once(&cfg.default_locale).chain(&cfg.available_locales);
+        for other in others.filter(|other| *other != locale) {
+            let candidates = candidates(Some(other));
+            if candidates.len() != 1 {
+                continue;
+            }
+            if let Some(path) = mirror_path(&candidates[0], other, locale, cfg.layout) {
+                return path;
+            }
+        }
+    }
+
+    let name = PathBuf::from(namespaced_name(namespace, locale));
+    match cfg.layout {
+        LocaleLayout::File => name,
+        LocaleLayout::Directory => Path::new(locale).join(name),
+    }
+}
+
+/// Path of the file of `locale` for the file of `other` locale, replacing the locale directory
+/// and the locale segment of the file name, `None` if the path has no locale in it.
+fn mirror_path(path: &Path, other: &str, locale: &str, layout: LocaleLayout) -> Option<PathBuf> {
+    let mut components = path
+        .iter()
+        .map(|s| s.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    let mut replaced = false;
+    if layout == LocaleLayout::Directory && components.len() > 1 && components[0] == other {
+        components[0] = locale.to_string();
+        replaced = true;
+    }
+    let name = components.last_mut()?;
+    let mut segments = name.split('.').collect::<Vec<_>>();
+    if segments.last() == Some(&other) {
+        segments.pop();
+        segments.push(locale);
+        *name = segments.join(".");
+        replaced = true;
+    }
+    replaced.then(|| components.iter().collect())
+}
+
+/// Path with the prefix added to its file name, e.g. `fr/admin/TODO.users`
+fn prefixed_path(path: &Path, filename_prefix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    path.with_file_name(format!("{filename_prefix}{name}"))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_namespace_file<P: AsRef<Path>>(
+    output: &P,
+    files: &[LocaleFile],
+    filename_prefix: &str,
+    namespace: &str,
+    cfg: &I18nConfig,
+    translations: &Translations,
+    meta: &Translations,
+    remove_empty: bool,
     msg: &dyn Fn(usize, &str),
 ) {
     match cfg.generate_version {
         1 => {
             for locale in &cfg.available_locales {
-                let file_data = translations.get(locale).unwrap();
-                if !file_data.is_empty() {
+                let file_data = translations.get(locale).cloned().unwrap_or_default();
+                let file_meta = meta.get(locale).cloned().unwrap_or_default();
+                let path = file_path(files, cfg, namespace, Some(locale));
+                let path = prefixed_path(&path, filename_prefix);
+                if file_data.is_empty() && file_meta.is_empty() {
+                    if remove_empty {
+                        remove_file_inner(output, cfg, &path);
+                    }
+                } else {
                     let mut file_data = file_data.clone();
                     file_data.sort_unstable_keys();
                     let file_data = FileVer1 {
                         _version: 1,
-                        translations: file_data,
+                        _meta: file_meta,
+                        translations: nest_lists(&file_data),
                     };
                     write_file_inner(
                         output,
                         file_data.translations.len(),
                         file_data,
                         cfg,
-                        filename_prefix,
-                        locale.as_str(),
+                        &path,
                         msg,
                     )
                 }
             }
         }
         2 => {
-            let translations = translations_transpose(translations);
-            if !translations.is_empty() {
+            let translations = translations
+                .iter()
+                .map(|(locale, trs)| {
+                    let mut trs = trs.clone();
+                    trs.sort_unstable_keys();
+                    (locale.clone(), nest_lists(&trs))
+                })
+                .collect();
+            let translations = translations_transpose(&translations);
+            let path = file_path(files, cfg, namespace, None);
+            let path = prefixed_path(&path, filename_prefix);
+            if translations.is_empty() && meta.is_empty() {
+                if remove_empty {
+                    remove_file_inner(output, cfg, &path);
+                }
+            } else {
                 let mut file_data = translations.clone();
                 file_data.sort_unstable_keys();
                 let file_data = FileVer2 {
                     _version: 2,
+                    _meta: meta.clone(),
                     translations: file_data,
                 };
                 write_file_inner(
@@ -214,8 +954,7 @@ fn write_file<P: AsRef<Path>>(
                     file_data.translations.len(),
                     file_data,
                     cfg,
-                    filename_prefix,
-                    "app",
+                    &path,
                     msg,
                 )
             }
@@ -224,103 +963,630 @@ fn write_file<P: AsRef<Path>>(
     }
 }
 
+/// Write a file, `path` is relative to `output` and without extension
 fn write_file_inner<IN: serde::Serialize, P: AsRef<Path>>(
     output: &P,
     count: usize,
     file_data: IN,
     cfg: &I18nConfig,
-    filename_prefix: &str,
-    filename_mid: &str,
+    path: &Path,
     msg: &dyn Fn(usize, &str),
 ) {
     let (file_string, file_ext) = format_data(&file_data, cfg.generate_extension.as_str());
-    let mut filename = String::from(filename_prefix);
-    filename.push_str(filename_mid);
-    filename.push_str(file_ext);
+    let filename = format!("{}{}", path.display(), file_ext);
 
-    let output_file = std::path::Path::new(output.as_ref()).join(filename.clone());
+    let output_file = output.as_ref().join(&filename);
+    if let Some(dir) = output_file.parent() {
+        std::fs::create_dir_all(dir)
+            .unwrap_or_else(|_| panic!("Unable to create {} dir", dir.display()));
+    }
     let mut output = ::std::fs::File::create(&output_file)
         .unwrap_or_else(|_| panic!("Unable to create {} file", &output_file.display()));
     msg(count, &filename);
     writeln!(output, "{}", file_string).expect("Write file error");
 }
-fn format_data<IN: serde::Serialize>(trs: &IN, format: &str) -> (String, &'static str) {
+
+/// Remove a file, that would be written without any translations
+fn remove_file_inner<P: AsRef<Path>>(output: &P, cfg: &I18nConfig, path: &Path) {
+    let filename = format!(
+        "{}{}",
+        path.display(),
+        file_extension(cfg.generate_extension.as_str())
+    );
+    let output_file = output.as_ref().join(filename);
+    if output_file.exists() {
+        std::fs::remove_file(&output_file)
+            .unwrap_or_else(|_| panic!("Unable to remove {} file", output_file.display()));
+    }
+}
+
+fn file_extension(format: &str) -> &'static str {
     match format {
-        "json" => (serde_json::to_string_pretty(trs).unwrap(), ".json"),
-        "toml" => (toml::to_string_pretty(trs).unwrap(), ".toml"),
-        "yaml" | "yml" | _ => (
-            {
-                let text = serde_yaml::to_string(trs).unwrap();
-                // Remove leading `---`
-                text.trim_start_matches("---").trim_start().to_string()
-            },
-            ".yml",
-        ),
-    }
-}
-
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use indoc::indoc;
-
-//     fn assert_eq_json(left: &str, right: &str) {
-//         let left: serde_json::Value = serde_json::from_str(left).unwrap();
-//         let right: serde_json::Value = serde_json::from_str(right).unwrap();
-//         assert_eq!(left, right);
-//     }
-
-//     #[test]
-//     fn test_convert_text() {
-//         let mut trs = Translations::new();
-//         let format = "json";
-
-//         let result = convert_text(&trs, format);
-//         let expect = r#"
-//         {
-//             "_version": 2
-//         }
-//         "#;
-//         assert_eq_json(&result, &expect);
-
-//         trs.insert("hello".to_string(), {
-//             let mut map = IndexMap::new();
-//             map.insert("en".to_string(), "Hello".to_string());
-//             map.insert("zh".to_string(), "你好".to_string());
-//             map
-//         });
-
-//         let result = convert_text(&trs, format);
-//         let expect = r#"
-//         {
-//             "_version": 2,
-//             "hello": {
-//                 "en": "Hello",
-//                 "zh": "你好"
-//             }
-//         }
-//         "#;
-//         assert_eq_json(&result, &expect);
-
-//         let format = "yaml";
-//         let result = convert_text(&trs, format);
-//         let expect = indoc! {r#"
-//         _version: 2
-//         hello:
-//           en: Hello
-//           zh: 你好
-//         "#};
-//         assert_eq!(&result, &expect);
-
-//         let format = "toml";
-//         let result = convert_text(&trs, format);
-//         let expect = indoc! {r#"
-//         _version = 2
-
-//         [hello]
-//         en = "Hello"
-//         zh = "你好"
-//         "#};
-//         assert_eq!(&result, &expect);
-//     }
-// }
+        "json" => ".json",
+        "toml" => ".toml",
+        _ => ".yml",
+    }
+}
+
+fn format_data<IN: serde::Serialize>(trs: &IN, format: &str) -> (String, &'static str) {
+    let text = match format {
+        "json" => serde_json::to_string_pretty(trs).unwrap(),
+        "toml" => toml::to_string_pretty(trs).unwrap(),
+        _ => {
+            let text = serde_yaml::to_string(trs).unwrap();
+            // Remove leading `---`
+            text.trim_start_matches("---").trim_start().to_string()
+        }
+    };
+    (text, file_extension(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty locales directory, removed when dropped, also if the test fails
+    fn locales_dir() -> tempfile::TempDir {
+        tempfile::Builder::new()
+            .prefix("rust-i18n-")
+            .tempdir()
+            .unwrap()
+    }
+
+    fn translations() -> Translations {
+        let en = [
+            ("hello", "Hello"),
+            ("hello world", "Hello world"),
+            ("a.very.nested.message", "Hello, %{name}!"),
+            ("welcome", "Welcome"),
+            ("welcome.sub", "Welcome 1"),
+            ("empty", ""),
+            ("yes", "yes"),
+            ("number", "123"),
+            ("float", "1.0"),
+            ("bool", "true"),
+            ("null", "null"),
+            ("tilde", "~"),
+            ("multiline", "Line 1\nLine 2\n"),
+            ("quotes", "\"quoted\" 'text' \\ # [x] {y}: z"),
+            ("spaces", "  spaces  "),
+            ("control", "tab\there\r\nend\u{1b}"),
+            ("leading_newline", "\nText"),
+            ("newline", "\n"),
+            (
+                "yaml_special",
+                "- *alias &anchor !tag @at %percent key: value #comment",
+            ),
+            ("toml_special", "''' \"\"\" '''"),
+            ("emoji", "👋 🌍"),
+            ("key: colon #hash [bracket] \"quoted\"", "Special key"),
+            ("", "Empty key"),
+            ("days.0", "Monday"),
+            ("days.1", "Tuesday"),
+            ("days.2", "Wednesday"),
+            ("days.3", "Thursday"),
+            ("days.4", "Friday"),
+            ("days.5", "Saturday"),
+            ("days.6", "Sunday"),
+            ("days.7", "8"),
+            ("days.8", "9"),
+            ("days.9", "10"),
+            ("days.10", "11"),
+            ("sparse.0", "First"),
+            ("sparse.2", "Third"),
+            ("errors.404", "Not found"),
+            ("steps.0.title", "Install"),
+            ("steps.1.title", "Run"),
+            ("steps.1.links.0", "Docs"),
+            ("steps.1.links.1", "Blog"),
+            ("123", "Number key"),
+            ("true", "Bool key"),
+            ("invite", "They invited you"),
+            ("invite.%{gender}.male", "He invited you"),
+            ("invite.%{gender}.other", "They invited you"),
+        ];
+        let zh = [
+            ("hello", "你好"),
+            ("welcome.sub", "欢迎 1"),
+            ("days.0", "星期一"),
+            ("days.1", "星期二"),
+        ];
+
+        IndexMap::from([
+            (
+                "en".to_string(),
+                en.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+            (
+                "zh-CN".to_string(),
+                zh.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+        ])
+    }
+
+    fn assert_round_trip(version: usize, extension: &str, layout: LocaleLayout) {
+        let dir = locales_dir();
+        let output = dir.path();
+
+        let cfg = I18nConfig {
+            available_locales: vec!["en".to_string(), "zh-CN".to_string()],
+            generate_version: version,
+            generate_extension: extension.to_string(),
+            generate_file_name: "messages".to_string(),
+            layout,
+            ..Default::default()
+        };
+        let translations = translations();
+        let meta = Translations::from([(
+            "zh-CN".to_string(),
+            IndexMap::from([("native-name".to_string(), "中文".to_string())]),
+        )]);
+        write_file(&output, "", &cfg, &translations, &meta, false, &|_, _| {});
+
+        let (mut loaded, loaded_meta) = load_locales_with_meta(
+            &output.display().to_string(),
+            |_| false,
+            &cfg.load_options(),
+        );
+        assert_eq!(loaded_meta, meta);
+        for (locale, trs) in &translations {
+            let mut loaded = loaded.swap_remove(locale).unwrap();
+            loaded.sort_unstable_keys();
+            let mut trs = trs.clone();
+            trs.sort_unstable_keys();
+            assert_eq!(
+                loaded, trs,
+                "version: {}, extension: {}, layout: {:?}, locale: {}",
+                version, extension, layout, locale
+            );
+        }
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn test_nest_lists() {
+        let trs = translations().swap_remove("en").unwrap();
+        let nested = nest_lists(&trs);
+        assert_eq!(nested["days"].as_array().unwrap().len(), 11);
+        assert_eq!(nested["days"][10], "11");
+        assert_eq!(nested["sparse.0"], "First");
+        assert_eq!(nested["errors.404"], "Not found");
+        assert_eq!(nested["steps"][0]["title"], "Install");
+        assert_eq!(nested["steps"][1]["links"][1], "Blog");
+        assert!(!nested.contains_key("steps.0.title"));
+        assert_eq!(nested["hello"], "Hello");
+        assert!(!nested.contains_key("days.0"));
+    }
+
+    #[test]
+    fn test_update_list() {
+        let en = translations().swap_remove("en").unwrap();
+        let mut data_done = Translations::from([("en".to_string(), en.clone())]);
+        let mut data_todo = Translations::new();
+        let mut data_removed = Translations::new();
+        let messages = ["days", "new.list"].map(|key| Message {
+            key: key.to_string(),
+            list: true,
+            ..Default::default()
+        });
+
+        for (locale, default_locale) in [("en", true), ("fr", false)] {
+            update_todo_done_removed(
+                &mut data_done,
+                &mut data_todo,
+                &mut data_removed,
+                &locale.to_string(),
+                &en,
+                &messages,
+                default_locale,
+            );
+        }
+
+        assert_eq!(list_item_keys(&data_done["en"], "days").len(), 11);
+        assert_eq!(data_todo["en"]["new.list.0"], "list");
+        assert!(!data_removed["en"].contains_key("days.0"));
+        assert_eq!(data_todo["fr"]["days.10"], "11");
+        assert_eq!(data_todo["fr"]["new.list.0"], "list");
+    }
+
+    #[test]
+    fn test_update_prefix() {
+        let trs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<IndexMap<_, _>>()
+        };
+        let en = trs(&[
+            ("status.active", "Active"),
+            ("status.disabled", "Disabled"),
+            ("unused", "Unused"),
+        ]);
+        let mut data_done = Translations::from([
+            ("en".to_string(), en.clone()),
+            ("fr".to_string(), trs(&[("status.active", "Actif")])),
+        ]);
+        let mut data_todo = Translations::new();
+        let mut data_removed = Translations::new();
+        let messages = [Message {
+            key: "status.".to_string(),
+            prefix: true,
+            ..Default::default()
+        }];
+
+        for (locale, default_locale) in [("en", true), ("fr", false)] {
+            update_todo_done_removed(
+                &mut data_done,
+                &mut data_todo,
+                &mut data_removed,
+                &locale.to_string(),
+                &en,
+                &messages,
+                default_locale,
+            );
+        }
+
+        assert_eq!(
+            data_done["en"].keys().collect::<Vec<_>>(),
+            ["status.active", "status.disabled"]
+        );
+        assert_eq!(data_removed["en"]["unused"], "Unused");
+        assert_eq!(data_done["fr"]["status.active"], "Actif");
+        assert_eq!(data_todo["fr"]["status.disabled"], "Disabled");
+        assert!(!data_todo["en"].contains_key("status."));
+    }
+
+    #[test]
+    fn test_generate_namespaced() {
+        let dir = locales_dir();
+        let output = dir.path();
+        std::fs::write(output.join("en.yml"), "hello: Hello").unwrap();
+        std::fs::write(output.join("views.en.yml"), "title: Title").unwrap();
+
+        let cfg = I18nConfig {
+            generate_version: 1,
+            namespaced: true,
+            ..Default::default()
+        };
+        let messages = ["hello", "views.title", "views.desc"].map(|key| Message {
+            key: key.to_string(),
+            ..Default::default()
+        });
+        generate(output, &cfg, &messages);
+
+        let read = |name: &str| std::fs::read_to_string(output.join(name)).unwrap();
+        assert_eq!(read("en.yml"), "_version: 1\nhello: Hello\n\n");
+        assert_eq!(read("views.en.yml"), "_version: 1\ntitle: Title\n\n");
+        assert_eq!(read("TODO.views.en.yml"), "_version: 1\ndesc: desc\n\n");
+        assert!(!output.join("TODO.en.yml").exists());
+
+        // Files without used keys are kept as they are
+        std::fs::write(output.join("legal.en.yml"), "terms: Terms").unwrap();
+        generate(output, &cfg, &messages);
+        assert_eq!(read("legal.en.yml"), "terms: Terms");
+        assert_eq!(read("views.en.yml"), "_version: 1\ntitle: Title\n\n");
+    }
+
+    #[test]
+    fn test_generate_keeps_file_paths() {
+        let dir = locales_dir();
+        let output = dir.path();
+        for locale in ["en", "fr"] {
+            std::fs::create_dir_all(output.join(locale).join("admin")).unwrap();
+        }
+        std::fs::write(output.join("en/admin/users.yml"), "title: Users").unwrap();
+        std::fs::write(output.join("fr/admin/users.yml"), "title: Utilisateurs").unwrap();
+
+        let cfg = I18nConfig {
+            available_locales: vec!["en".to_string(), "fr".to_string(), "de".to_string()],
+            generate_version: 1,
+            namespaced: true,
+            layout: LocaleLayout::Directory,
+            ..Default::default()
+        };
+        let messages = ["admin.users.title", "admin.users.desc"].map(|key| Message {
+            key: key.to_string(),
+            ..Default::default()
+        });
+        generate(output, &cfg, &messages);
+
+        let read = |name: &str| std::fs::read_to_string(output.join(name)).unwrap();
+        assert_eq!(read("en/admin/users.yml"), "_version: 1\ntitle: Users\n\n");
+        assert_eq!(
+            read("fr/admin/users.yml"),
+            "_version: 1\ntitle: Utilisateurs\n\n"
+        );
+        assert_eq!(
+            read("fr/admin/TODO.users.yml"),
+            "_version: 1\ndesc: desc\n\n"
+        );
+        assert_eq!(
+            read("de/admin/TODO.users.yml"),
+            "_version: 1\ndesc: desc\ntitle: Users\n\n"
+        );
+        assert!(!output.join("fr/admin.users.fr.yml").exists());
+
+        let dir = locales_dir();
+        let output = dir.path();
+        std::fs::create_dir_all(output.join("pages")).unwrap();
+        std::fs::write(
+            output.join("pages/views.app.yml"),
+            "_version: 2\ntitle:\n  en: Title\n",
+        )
+        .unwrap();
+
+        let cfg = I18nConfig {
+            generate_version: 2,
+            namespaced: true,
+            ..Default::default()
+        };
+        let messages = ["views.title"].map(|key| Message {
+            key: key.to_string(),
+            ..Default::default()
+        });
+        generate(output, &cfg, &messages);
+        let read = |name: &str| std::fs::read_to_string(output.join(name)).unwrap();
+        assert_eq!(
+            read("pages/views.app.yml"),
+            "_version: 2\ntitle:\n  en: Title\n\n"
+        );
+        assert!(!output.join("views.app.yml").exists());
+    }
+
+    #[test]
+    fn test_rename() {
+        let dir = locales_dir();
+        let output = dir.path();
+        std::fs::write(output.join("en.yml"), "hello: Hello").unwrap();
+        std::fs::write(output.join("views.en.yml"), "title: Title").unwrap();
+        std::fs::write(output.join("views.fr.yml"), "title: Titre").unwrap();
+        std::fs::write(output.join("views.de.yml"), "title: Titel").unwrap();
+        std::fs::write(output.join("TODO.fr.yml"), "views.desc: Description").unwrap();
+
+        let cfg = I18nConfig {
+            available_locales: vec!["en".to_string(), "fr".to_string()],
+            generate_version: 1,
+            namespaced: true,
+            ..Default::default()
+        };
+        let rename = KeyRename::new("views.*", "pages.*").unwrap();
+        assert_eq!(super::rename(output, &cfg, &rename), Ok(4));
+
+        let loaded = load_locales_with_options(
+            &output.display().to_string(),
+            |_| false,
+            &cfg.load_options(),
+        );
+        assert_eq!(loaded["en"]["pages.title"], "Title");
+        assert_eq!(loaded["fr"]["pages.title"], "Titre");
+        assert_eq!(loaded["fr"]["pages.desc"], "Description");
+        assert_eq!(loaded["de"]["pages.title"], "Titel");
+        assert!(!loaded["en"].contains_key("views.title"));
+        assert!(!loaded["de"].contains_key("views.title"));
+        assert!(!output.join("views.en.yml").exists());
+
+        let rename = KeyRename::new("hello", "pages.title").unwrap();
+        assert!(super::rename(output, &cfg, &rename).is_err());
+    }
+
+    #[test]
+    fn test_stale_translations() {
+        let dir = locales_dir();
+        let output = dir.path();
+        std::fs::write(output.join("en.yml"), "hello: Hello\nbye: Bye").unwrap();
+        std::fs::write(output.join("fr.yml"), "hello: Bonjour\nbye: Au revoir").unwrap();
+
+        let cfg = I18nConfig {
+            available_locales: vec!["en".to_string(), "fr".to_string()],
+            generate_version: 1,
+            ..Default::default()
+        };
+        let messages = ["hello", "bye"].map(|key| Message {
+            key: key.to_string(),
+            ..Default::default()
+        });
+        let load = |ignore_if: fn(&str) -> bool| {
+            load_locales_with_options(
+                &output.display().to_string(),
+                ignore_if,
+                &cfg.load_options(),
+            )
+        };
+        let todo = |fname: &str| !fname.contains("TODO");
+        let todo_fr = |key: &str| {
+            let todo = load(todo);
+            todo.get("fr").and_then(|trs| trs.get(key).cloned())
+        };
+        let done = |fname: &str| fname.contains("TODO") || fname.contains("REMOVED");
+
+        generate(output, &cfg, &messages);
+        assert!(output.join(SNAPSHOT_FILE_NAME).exists());
+        assert_eq!(load(done)["fr"]["hello"], "Bonjour");
+
+        std::fs::write(output.join("en.yml"), "hello: Hello!\nbye: Bye").unwrap();
+        generate(output, &cfg, &messages);
+        assert_eq!(
+            todo_fr("hello").unwrap(),
+            "STALE Bonjour [old: Hello, new: Hello!]"
+        );
+        assert_eq!(todo_fr("bye"), None);
+        // Still in use, until translated again
+        assert_eq!(load(done)["fr"]["hello"], "Bonjour");
+        assert_eq!(load(done)["fr"]["bye"], "Au revoir");
+
+        // Kept until translated again
+        generate(output, &cfg, &messages);
+        assert_eq!(
+            todo_fr("hello").unwrap(),
+            "STALE Bonjour [old: Hello, new: Hello!]"
+        );
+
+        // Confirmed unchanged in TODO, with or without the old and the new text
+        for (text, confirmed) in [
+            ("Hello!", "DONE Bonjour [old: Hello, new: Hello!]"),
+            ("Hello!!", "DONE Bonjour"),
+        ] {
+            std::fs::write(output.join("en.yml"), format!("hello: {text}\nbye: Bye")).unwrap();
+            generate(output, &cfg, &messages);
+            assert!(todo_fr("hello").unwrap().starts_with("STALE Bonjour"));
+
+            std::fs::write(output.join("TODO.fr.yml"), format!("hello: {confirmed:?}")).unwrap();
+            generate(output, &cfg, &messages);
+            assert_eq!(todo_fr("hello"), None);
+            assert_eq!(load(done)["fr"]["hello"], "Bonjour");
+            generate(output, &cfg, &messages);
+            assert_eq!(todo_fr("hello"), None);
+        }
+
+        // Translated again in the file
+        std::fs::write(output.join("en.yml"), "hello: Hello!\nbye: Bye").unwrap();
+        std::fs::write(output.join("fr.yml"), "hello: Bonjour !\nbye: Au revoir").unwrap();
+        generate(output, &cfg, &messages);
+        assert_eq!(todo_fr("hello"), None);
+
+        // Translation updated together with its text is not stale
+        std::fs::write(output.join("en.yml"), "hello: Hello!\nbye: Goodbye").unwrap();
+        std::fs::write(output.join("fr.yml"), "hello: Bonjour !\nbye: Adieu").unwrap();
+        generate(output, &cfg, &messages);
+        assert_eq!(todo_fr("bye"), None);
+
+        // Translation added after the snapshot is checked too
+        std::fs::write(
+            output.join("en.yml"),
+            "hello: Hello!\nbye: Goodbye\nyes: Yes",
+        )
+        .unwrap();
+        let messages = ["hello", "bye", "yes"].map(|key| Message {
+            key: key.to_string(),
+            ..Default::default()
+        });
+        generate(output, &cfg, &messages);
+        std::fs::write(
+            output.join("fr.yml"),
+            "hello: Bonjour !\nbye: Adieu\nyes: Oui",
+        )
+        .unwrap();
+        generate(output, &cfg, &messages);
+        std::fs::write(
+            output.join("en.yml"),
+            "hello: Hello!\nbye: Goodbye\nyes: Yes!",
+        )
+        .unwrap();
+        generate(output, &cfg, &messages);
+        assert_eq!(todo_fr("yes").unwrap(), "STALE Oui [old: Yes, new: Yes!]");
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Hello", "hello"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0);
+        assert_eq!(similarity("abc", ""), 0.0);
+
+        let memory = [
+            ("Save the file", "Enregistrer le fichier"),
+            ("Save the files", "Enregistrer les fichiers"),
+            ("Open", "Ouvrir"),
+        ];
+        assert_eq!(
+            fuzzy_suggestion(&memory, "Save the files!"),
+            Some("Enregistrer les fichiers")
+        );
+        assert_eq!(fuzzy_suggestion(&memory, "Close"), None);
+    }
+
+    #[test]
+    fn test_update_fuzzy() {
+        let en = IndexMap::from([
+            ("save".to_string(), "Save the file".to_string()),
+            ("save_all".to_string(), "Save all files".to_string()),
+        ]);
+        let mut data_done = Translations::from([
+            ("en".to_string(), en.clone()),
+            (
+                "fr".to_string(),
+                IndexMap::from([("save".to_string(), "Enregistrer le fichier".to_string())]),
+            ),
+        ]);
+        let mut data_todo = Translations::new();
+        let mut data_removed = Translations::new();
+        let messages = ["save", "save_all", "save_file", "quit"].map(|key| Message {
+            key: key.to_string(),
+            ..Default::default()
+        });
+        let mut messages = messages.to_vec();
+        messages[2].val = Some("Save the files".to_string());
+
+        for (locale, default_locale) in [("en", true), ("fr", false)] {
+            let default_val = data_done["en"].clone();
+            update_todo_done_removed(
+                &mut data_done,
+                &mut data_todo,
+                &mut data_removed,
+                &locale.to_string(),
+                &default_val,
+                &messages,
+                default_locale,
+            );
+        }
+
+        assert_eq!(data_todo["fr"]["save_file"], "FUZZY Enregistrer le fichier");
+        assert_eq!(data_todo["fr"]["save_all"], "Save all files");
+        assert_eq!(data_todo["fr"]["quit"], "quit");
+        assert_eq!(data_done["en"]["save_file"], "Save the files");
+    }
+
+    #[test]
+    fn test_update_variants() {
+        let en = translations().swap_remove("en").unwrap();
+        let mut data_done = Translations::from([
+            ("en".to_string(), en.clone()),
+            (
+                "fr".to_string(),
+                IndexMap::from([("invite.%{gender}.male".to_string(), "Il".to_string())]),
+            ),
+        ]);
+        let mut data_todo = Translations::new();
+        let mut data_removed = Translations::new();
+        let messages = [Message {
+            key: "invite".to_string(),
+            ..Default::default()
+        }];
+
+        for (locale, default_locale) in [("en", true), ("fr", false)] {
+            update_todo_done_removed(
+                &mut data_done,
+                &mut data_todo,
+                &mut data_removed,
+                &locale.to_string(),
+                &en,
+                &messages,
+                default_locale,
+            );
+        }
+
+        assert_eq!(data_done["en"]["invite.%{gender}.male"], "He invited you");
+        assert!(!data_removed["en"].contains_key("invite.%{gender}.other"));
+        assert_eq!(data_done["fr"]["invite.%{gender}.male"], "Il");
+        assert_eq!(
+            data_todo["fr"]["invite.%{gender}.other"],
+            "They invited you"
+        );
+        assert!(!data_todo["fr"].contains_key("invite.%{gender}.male"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for version in [1, 2] {
+            for extension in ["yml", "json", "toml"] {
+                for layout in [LocaleLayout::File, LocaleLayout::Directory] {
+                    assert_round_trip(version, extension, layout);
+                }
+            }
+        }
+    }
+}
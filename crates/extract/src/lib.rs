@@ -2,6 +2,8 @@
 use anyhow::Error;
 use clap::Parser;
 use indexmap::IndexMap;
+use rust_i18n_support::config::{self, I18nConfig};
+use rust_i18n_support::export;
 use serde_derive::Deserialize;
 use std::fs::File;
 use std::io::{self, BufRead, ErrorKind, Read, Write};
@@ -11,6 +13,8 @@ use std::{env, fs};
 
 pub mod extractor;
 pub mod generator;
+pub mod lint;
+pub mod rename;
 
 const ABOUT: &str = r#"Rust I18n command for help you simply to extract all untranslated texts from source code.
 
@@ -36,7 +40,7 @@ pub enum Subcommand {
     I18N(I18N),
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 pub struct I18N {
     /// Print command lines as they are executed
     #[arg(long)]
@@ -54,10 +58,22 @@ pub struct I18N {
     #[arg(long, value_name = "DIRECTORY")]
     pub locales_dir: Option<PathBuf>,
 
+    /// Macro whose argument is a key, besides `t!`, added to `extract-macros` of the config
+    #[arg(long, value_name = "NAME[:POSITION]")]
+    pub extract_macro: Vec<String>,
+
+    /// Function whose argument is a key, added to `extract-functions` of the config
+    #[arg(long, value_name = "NAME[:POSITION]")]
+    pub extract_function: Vec<String>,
+
     /// Package to expand
     #[arg(short, long, value_name = "SPEC", num_args = 0..=1, help_heading = PACKAGE_SELECTION)]
     pub package: Option<Option<String>>,
 
+    /// Extract all packages of the workspace, each into the locales directory of its config
+    #[arg(long, help_heading = PACKAGE_SELECTION)]
+    pub workspace: bool,
+
     /// Expand only this package's library
     #[arg(long, help_heading = TARGET_SELECTION)]
     pub lib: bool,
@@ -125,19 +141,85 @@ pub struct I18N {
     /// Run without accessing the network
     #[arg(long, help_heading = MANIFEST_OPTIONS)]
     pub offline: bool,
+
+    #[command(subcommand)]
+    pub command: Option<I18nCommand>,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum I18nCommand {
+    /// Export translations for use by other i18n libraries
+    Export(ExportArgs),
+    /// Rename a key, or keys with a prefix, in all locale files and in `t!` calls of the source code
+    Rename(RenameArgs),
+    /// Check translations against the default locale, e.g. for missing placeholders
+    Lint(LintArgs),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ExportArgs {
+    /// Format of exported translations
+    #[arg(long, value_enum, default_value_t = ExportFormat::I18next)]
+    pub format: ExportFormat,
+
+    /// Directory for exported translations
+    #[arg(long, value_name = "DIRECTORY", default_value = "i18next")]
+    pub output: PathBuf,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct RenameArgs {
+    /// Key to rename, or a prefix of keys ending with `*`, e.g. `views.*`
+    pub old: String,
+
+    /// New key, or the new prefix ending with `*`, e.g. `pages.*`
+    pub new: String,
+
+    /// Only rename keys in locale files, not in the source code
+    #[arg(long)]
+    pub no_source: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct LintArgs {
+    /// Format of the report
+    #[arg(long, value_enum, default_value_t = LintFormat::Text)]
+    pub format: LintFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum LintFormat {
+    /// One line per issue
+    Text,
+    /// JSON array of issues, with `rule`, `severity`, `locale`, `key` and `message`
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    /// i18next JSON v4, `<output>/<locale>/<namespace>.json`
+    I18next,
 }
 
 pub fn extractor_main() -> Result<(), Error> {
     let Subcommand::I18N(args) = Subcommand::parse();
 
-    let current_path: PathBuf = ".".into();
-    let cfg = rust_i18n_support::config::load(
-        &args.manifest_path.clone().unwrap_or(current_path.clone()),
-    )?;
+    let cfg = config::load(&manifest_dir(args.manifest_path.as_deref()))?;
     let locales_dir = args
         .locales_dir
         .clone()
         .unwrap_or(cfg.load_path.clone().into());
+    let options = extract_options(&cfg, &args)?;
+
+    match &args.command {
+        Some(I18nCommand::Export(export_args)) => return export(&locales_dir, export_args),
+        Some(I18nCommand::Rename(rename_args)) => {
+            let package_dir = manifest_dir(args.manifest_path.as_deref());
+            return rename(&locales_dir, &cfg, &package_dir, &options, rename_args);
+        }
+        Some(I18nCommand::Lint(lint_args)) => return lint(&locales_dir, &cfg, lint_args),
+        None => {}
+    }
 
     let temp_path = args
         .target_dir
@@ -161,20 +243,28 @@ pub fn extractor_main() -> Result<(), Error> {
         })
         .join("temp___i18n_macro_expansion.rs");
 
-    // expand macros by running cargo
-    let mut cmd = Command::new(cargo_binary());
-    apply_args(&mut cmd, &args, &temp_path);
-    cmd.env("RUSTC_BOOTSTRAP", "1");
+    if args.workspace {
+        extract_workspace(&args, &temp_path)?;
+        std::process::exit(0);
+    }
 
-    if 0 == filter_err(&mut cmd)? {
-        //read expanded file
-        let mut s = String::new();
-        let mut f = File::open(&temp_path).expect(&format!("Failed to open file: {:?}", temp_path));
-        f.read_to_string(&mut s).expect("Failed to read file");
+    if let Some(s) = expand(&args, &temp_path)? {
+        let package_dir = manifest_dir(args.manifest_path.as_deref());
+        let package_dir = dunce::canonicalize(&package_dir).unwrap_or(package_dir);
+        let source_roots = cargo_metadata(&args)?
+            .workspace_packages()
+            .find(|p| p.manifest_path.parent() == Some(package_dir.as_path()))
+            .map(MetadataPackage::source_roots)
+            .unwrap_or_default();
 
         //process file
         let mut results = IndexMap::new();
-        extractor::extract(&mut results, &temp_path, &s)?;
+        extractor::extract(&mut results, &temp_path, &s, &options)?;
+        report_dynamic_keys(&extractor::extract_sources(
+            &mut results,
+            &source_roots,
+            &options,
+        )?);
 
         let mut messages: Vec<_> = results.values().collect();
         messages.sort_by_key(|m| m.index);
@@ -185,6 +275,295 @@ pub fn extractor_main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Directory of the manifest given by `--manifest-path`, or the current directory
+fn manifest_dir(manifest_path: Option<&Path>) -> PathBuf {
+    match manifest_path.and_then(Path::parent) {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => ".".into(),
+    }
+}
+
+/// Expand macros by running cargo, return the expanded source or `None` if cargo failed
+fn expand(args: &I18N, temp_path: &Path) -> Result<Option<String>, Error> {
+    let mut cmd = Command::new(cargo_binary());
+    apply_args(&mut cmd, args, temp_path);
+    cmd.env("RUSTC_BOOTSTRAP", "1");
+
+    if 0 != filter_err(&mut cmd)? {
+        return Ok(None);
+    }
+
+    //read expanded file
+    let mut s = String::new();
+    let mut f =
+        File::open(temp_path).unwrap_or_else(|_| panic!("Failed to open file: {:?}", temp_path));
+    f.read_to_string(&mut s).expect("Failed to read file");
+    Ok(Some(s))
+}
+
+/// Extract every workspace member using rust-i18n, members with the same locales directory are merged.
+fn extract_workspace(args: &I18N, temp_path: &Path) -> Result<(), Error> {
+    let metadata = cargo_metadata(args)?;
+    let mut groups: IndexMap<PathBuf, (I18nConfig, extractor::Results)> = IndexMap::new();
+    let mut dynamic_keys = vec![];
+
+    for package in metadata.workspace_packages() {
+        if !package.dependencies.iter().any(|d| d.name == "rust-i18n") {
+            continue;
+        }
+
+        let package_dir = manifest_dir(Some(&package.manifest_path));
+        let cfg = config::load(&package_dir)?;
+        let options = extract_options(&cfg, args)?;
+        let locales_dir = package_dir.join(&cfg.load_path);
+        let locales_dir = dunce::canonicalize(&locales_dir).unwrap_or(locales_dir);
+
+        let (_, results) = groups
+            .entry(locales_dir)
+            .or_insert_with(|| (cfg, IndexMap::new()));
+
+        for target in &package.targets {
+            let mut target_args = args.clone();
+            target_args.package = Some(Some(package.name.clone()));
+            target_args.lib = target.kind.iter().any(|k| k == "lib");
+            target_args.bin = if target.kind.iter().any(|k| k == "bin") {
+                Some(Some(target.name.clone()))
+            } else {
+                None
+            };
+            if !target_args.lib && target_args.bin.is_none() {
+                continue;
+            }
+
+            eprintln!("Expanding [{}] {}...", package.name, target.name);
+            if let Some(s) = expand(&target_args, temp_path)? {
+                extractor::extract(results, &temp_path.to_path_buf(), &s, &options)?;
+            }
+        }
+
+        // A member can be nested in the source directory of another package
+        for key in extractor::extract_sources(results, &package.source_roots(), &options)? {
+            if !dynamic_keys.contains(&key) {
+                dynamic_keys.push(key);
+            }
+        }
+    }
+
+    report_dynamic_keys(&dynamic_keys);
+
+    for (locales_dir, (cfg, results)) in &groups {
+        eprintln!("\nGenerating {}...", locales_dir.display());
+
+        let mut messages: Vec<_> = results.values().collect();
+        messages.sort_by_key(|m| m.index);
+
+        generator::generate(locales_dir, cfg, messages.clone());
+    }
+
+    Ok(())
+}
+
+/// Macros and functions with a key argument, from the config and the command line
+fn extract_options(cfg: &I18nConfig, args: &I18N) -> Result<extractor::ExtractOptions, Error> {
+    let mut cfg = cfg.clone();
+    cfg.extract_macros
+        .extend(args.extract_macro.iter().cloned());
+    cfg.extract_functions
+        .extend(args.extract_function.iter().cloned());
+    extractor::ExtractOptions::from_config(&cfg).map_err(Error::msg)
+}
+
+/// Warn about `t!` calls with non-literal keys, their keys are only kept by `keep_keys!`
+fn report_dynamic_keys(dynamic_keys: &[extractor::DynamicKey]) {
+    for key in dynamic_keys {
+        eprintln!(
+            "warning: key `{}` is not a literal, it can not be extracted\n  --> {}:{}:{}",
+            key.expr,
+            key.location.file.display(),
+            key.location.line,
+            key.location.column + 1
+        );
+    }
+    if !dynamic_keys.is_empty() {
+        eprintln!(
+            "Keys that are not used as literals are moved to REMOVED, \
+             keep them with `rust_i18n::keep_keys!(\"key\", ...)` or `rust_i18n::keep_keys!(prefix = \"prefix.\")`.\n"
+        );
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CargoMetadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+}
+
+impl CargoMetadata {
+    fn workspace_packages(&self) -> impl Iterator<Item = &MetadataPackage> {
+        self.packages
+            .iter()
+            .filter(|p| self.workspace_members.contains(&p.id))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    manifest_path: PathBuf,
+    targets: Vec<MetadataTarget>,
+    dependencies: Vec<MetadataDependency>,
+}
+
+impl MetadataPackage {
+    /// Directories of the lib and bin targets, without examples, tests and benches
+    fn source_roots(&self) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = vec![];
+        for target in &self.targets {
+            if !target.kind.iter().any(|k| k == "lib" || k == "bin") {
+                continue;
+            }
+            let Some(dir) = target.src_path.parent() else {
+                continue;
+            };
+            if roots.iter().any(|root| dir.starts_with(root)) {
+                continue;
+            }
+            roots.retain(|root| !root.starts_with(dir));
+            roots.push(dir.to_path_buf());
+        }
+        roots
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct MetadataTarget {
+    name: String,
+    kind: Vec<String>,
+    src_path: PathBuf,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetadataDependency {
+    name: String,
+}
+
+fn cargo_metadata(args: &I18N) -> Result<CargoMetadata, Error> {
+    let mut cmd = Command::new(cargo_binary());
+    cmd.arg("metadata")
+        .arg("--no-deps")
+        .arg("--format-version")
+        .arg("1");
+
+    if let Some(manifest_path) = &args.manifest_path {
+        cmd.arg("--manifest-path");
+        cmd.arg(manifest_path);
+    }
+    if args.frozen {
+        cmd.arg("--frozen");
+    }
+    if args.locked {
+        cmd.arg("--locked");
+    }
+    if args.offline {
+        cmd.arg("--offline");
+    }
+
+    let output = cmd.stderr(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        anyhow::bail!("Failed to run `cargo metadata`");
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+fn export(locales_dir: &Path, args: &ExportArgs) -> Result<(), Error> {
+    let locales_path = locales_dir.display().to_string();
+
+    match args.format {
+        ExportFormat::I18next => {
+            for (namespace, translations) in export::load_i18next_namespaces(&locales_path) {
+                export::write_i18next(&args.output, &namespace, &translations)?;
+                eprintln!(
+                    "Exported namespace [{}] ({} locales) to {}",
+                    namespace,
+                    translations.len(),
+                    args.output.display()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn rename(
+    locales_dir: &Path,
+    cfg: &I18nConfig,
+    package_dir: &Path,
+    options: &extractor::ExtractOptions,
+    args: &RenameArgs,
+) -> Result<(), Error> {
+    let key_rename = rename::KeyRename::new(&args.old, &args.new).map_err(Error::msg)?;
+
+    let count = generator::rename(locales_dir, cfg, &key_rename).map_err(Error::msg)?;
+    eprintln!(
+        "Renamed {} translations in {}",
+        count,
+        locales_dir.display()
+    );
+
+    if !args.no_source {
+        let (calls, files) = rename::rename_in_sources(package_dir, &key_rename, options)?;
+        eprintln!("Renamed {} keys of `t!` in {} files", calls, files);
+    }
+
+    Ok(())
+}
+
+fn lint(locales_dir: &Path, cfg: &I18nConfig, args: &LintArgs) -> Result<(), Error> {
+    let severities = lint::severities(&cfg.lint).map_err(Error::msg)?;
+    let ignore_file_ndone = |fname: &str| fname.contains("TODO") || fname.contains("REMOVED");
+    let translations = rust_i18n_support::load_locales_with_options(
+        &locales_dir.display().to_string(),
+        ignore_file_ndone,
+        &cfg.load_options(),
+    );
+    let issues = lint::lint(&translations, &cfg.default_locale, &severities);
+
+    match args.format {
+        LintFormat::Json => println!("{}", serde_json::to_string_pretty(&issues)?),
+        LintFormat::Text => {
+            for issue in &issues {
+                println!(
+                    "{}[{}] {} `{}`: {}",
+                    issue.severity.as_str(),
+                    issue.rule,
+                    issue.locale,
+                    issue.key,
+                    issue.message
+                );
+            }
+        }
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == config::Severity::Error)
+        .count();
+    if errors > 0 {
+        anyhow::bail!(
+            "Found {} errors and {} warnings",
+            errors,
+            issues.len() - errors
+        );
+    }
+    if !issues.is_empty() {
+        eprintln!("Found {} warnings", issues.len());
+    }
+
+    Ok(())
+}
+
 fn cargo_binary() -> std::ffi::OsString {
     std::env::var_os("CARGO").unwrap_or_else(|| "cargo".to_owned().into())
 }
@@ -388,3 +767,73 @@ fn find_cargo_manifest(manifest_path: Option<&Path>) -> io::Result<PathBuf> {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_custom_macros() {
+        let package_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/custom-macro");
+        let target_dir = tempfile::tempdir().unwrap();
+        let args = I18N::try_parse_from([
+            "i18n".as_ref(),
+            "--offline".as_ref(),
+            "--manifest-path".as_ref(),
+            package_dir.join("Cargo.toml").as_os_str(),
+            "--target-dir".as_ref(),
+            target_dir.path().as_os_str(),
+        ])
+        .unwrap();
+        let temp_path = target_dir.path().join("temp___i18n_macro_expansion.rs");
+        let expanded = expand(&args, &temp_path).unwrap().unwrap();
+
+        let options = extractor::ExtractOptions {
+            macros: vec!["tr:1".parse().unwrap()],
+            functions: vec!["caption:1".parse().unwrap()],
+        };
+        let mut results = IndexMap::new();
+        extractor::extract(&mut results, &temp_path, &expanded, &options).unwrap();
+        // `tr!` is expanded away, functions are kept
+        assert_eq!(results.keys().collect::<Vec<_>>(), ["views.title"]);
+
+        let dynamic_keys =
+            extractor::extract_sources(&mut results, &[package_dir.join("src")], &options).unwrap();
+        assert_eq!(
+            results.keys().collect::<Vec<_>>(),
+            ["views.title", "views.save"]
+        );
+        assert_eq!(results["views.title"].locations.len(), 1);
+        assert_eq!(results["views.save"].locations[0].line, 9);
+        let dynamic_keys: Vec<_> = dynamic_keys
+            .iter()
+            .map(|k| (k.location.line, k.expr.as_str()))
+            .collect();
+        assert_eq!(dynamic_keys, [(13, "key"), (21, "key")]);
+    }
+
+    #[test]
+    fn test_source_roots() {
+        let package: MetadataPackage = serde_json::from_value(serde_json::json!({
+            "id": "foo 0.1.0",
+            "name": "foo",
+            "manifest_path": "/foo/Cargo.toml",
+            "dependencies": [],
+            "targets": [
+                { "name": "foo", "kind": ["lib"], "src_path": "/foo/src/lib.rs" },
+                { "name": "foo", "kind": ["bin"], "src_path": "/foo/src/main.rs" },
+                { "name": "cli", "kind": ["bin"], "src_path": "/foo/src/bin/cli.rs" },
+                { "name": "tool", "kind": ["bin"], "src_path": "/foo/tool/main.rs" },
+                { "name": "demo", "kind": ["example"], "src_path": "/foo/examples/demo.rs" },
+                { "name": "it", "kind": ["test"], "src_path": "/foo/tests/it.rs" },
+                { "name": "bench", "kind": ["bench"], "src_path": "/foo/benches/bench.rs" }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            package.source_roots(),
+            vec![PathBuf::from("/foo/src"), PathBuf::from("/foo/tool")]
+        );
+    }
+}
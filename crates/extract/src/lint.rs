@@ -0,0 +1,387 @@
+use indexmap::IndexMap;
+use rust_i18n_support::config::Severity;
+use serde_derive::Serialize;
+
+type Translations = IndexMap<String, IndexMap<String, String>>;
+
+/// Rules of `cargo i18n lint`, with their default severity
+pub const RULES: &[(&str, Severity)] = &[
+    ("placeholders", Severity::Error),
+    ("markup", Severity::Error),
+    ("whitespace", Severity::Warning),
+    ("quotes", Severity::Warning),
+    ("untranslated", Severity::Warning),
+    ("punctuation", Severity::Warning),
+];
+
+/// A problem of a translation, compared to the text of the default locale
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub locale: String,
+    pub key: String,
+    pub message: String,
+}
+
+/// Severity of each rule, overridden by `lint` of the I18n config
+pub fn severities(
+    config: &IndexMap<String, Severity>,
+) -> Result<Vec<(&'static str, Severity)>, String> {
+    if let Some(rule) = config
+        .keys()
+        .find(|rule| !RULES.iter().any(|(name, _)| name == rule))
+    {
+        let names: Vec<_> = RULES.iter().map(|(name, _)| *name).collect();
+        return Err(format!(
+            "Unknown lint rule `{}`, expected one of {}",
+            rule,
+            names.join(", ")
+        ));
+    }
+
+    Ok(RULES
+        .iter()
+        .map(|(rule, severity)| (*rule, *config.get(*rule).unwrap_or(severity)))
+        .collect())
+}
+
+/// Check translations of all locales against the texts of the default locale
+pub fn lint(
+    translations: &Translations,
+    default_locale: &str,
+    severities: &[(&'static str, Severity)],
+) -> Vec<Issue> {
+    let Some(default_trs) = translations.get(default_locale) else {
+        return vec![];
+    };
+    let language = |locale: &str| {
+        locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let mut issues = vec![];
+    for (locale, trs) in translations {
+        if locale == default_locale {
+            continue;
+        }
+        let same_language = language(locale) == language(default_locale);
+
+        for (key, text) in trs {
+            let Some(source) = default_trs.get(key) else {
+                continue;
+            };
+
+            for (rule, severity) in severities {
+                if *severity == Severity::Off || (*rule == "untranslated" && same_language) {
+                    continue;
+                }
+                for message in check(rule, source, text) {
+                    issues.push(Issue {
+                        rule,
+                        severity: *severity,
+                        locale: locale.clone(),
+                        key: key.clone(),
+                        message,
+                    });
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Problems of the translation by the rule, compared to the source text
+fn check(rule: &str, source: &str, text: &str) -> Vec<String> {
+    match rule {
+        "placeholders" => {
+            let (source, text) = (placeholders(source), placeholders(text));
+            let mut messages = vec![];
+            for name in missing(&source, &text) {
+                messages.push(format!("missing placeholder `{}`", name));
+            }
+            for name in missing(&text, &source) {
+                messages.push(format!("unknown placeholder `{}`", name));
+            }
+            messages
+        }
+        "markup" => {
+            let (source, text) = (markup(source), markup(text));
+            let mut messages = vec![];
+            for tag in missing(&source, &text) {
+                messages.push(format!("missing markup `{}`", tag));
+            }
+            for tag in missing(&text, &source) {
+                messages.push(format!("extra markup `{}`", tag));
+            }
+            messages
+        }
+        "whitespace" => {
+            let mut messages = vec![];
+            let leading = |s: &str| s.len() - s.trim_start().len();
+            let trailing = |s: &str| s.len() - s.trim_end().len();
+            if source[..leading(source)] != text[..leading(text)] {
+                messages.push("leading whitespace differs from the default locale".to_string());
+            }
+            if source[source.len() - trailing(source)..] != text[text.len() - trailing(text)..] {
+                messages.push("trailing whitespace differs from the default locale".to_string());
+            }
+            messages
+        }
+        "quotes" => {
+            if unbalanced_quotes(source).is_some() {
+                return vec![];
+            }
+            match unbalanced_quotes(text) {
+                Some(quote) => vec![format!("unbalanced quotes `{}`", quote)],
+                None => vec![],
+            }
+        }
+        "untranslated" => {
+            let words = placeholders(text)
+                .iter()
+                .fold(text.to_string(), |text, placeholder| {
+                    text.replace(placeholder, "")
+                });
+            if text == source && words.chars().any(|c| c.is_alphabetic()) {
+                vec!["same text as the default locale".to_string()]
+            } else {
+                vec![]
+            }
+        }
+        "punctuation" => {
+            let (source_end, text_end) = (end_punctuation(source), end_punctuation(text));
+            if source_end == text_end {
+                return vec![];
+            }
+            let describe = |end: Option<char>| match end {
+                Some(c) => format!("`{}`", c),
+                None => "no punctuation".to_string(),
+            };
+            vec![format!(
+                "ends with {}, but the default locale with {}",
+                describe(text_end),
+                describe(source_end)
+            )]
+        }
+        _ => vec![],
+    }
+}
+
+/// Items of `a` that are missing in `b`, counting duplicates
+fn missing(a: &[String], b: &[String]) -> Vec<String> {
+    let mut b = b.to_vec();
+    let mut result = vec![];
+    for item in a {
+        match b.iter().position(|x| x == item) {
+            Some(i) => {
+                b.swap_remove(i);
+            }
+            None => result.push(item.clone()),
+        }
+    }
+    result
+}
+
+/// Placeholders `%{name}` of the text
+fn placeholders(text: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("%{") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        result.push(rest[start..start + len + 1].to_string());
+        rest = &rest[start + len + 1..];
+    }
+    result
+}
+
+/// HTML tags (`<b>`, `</b>`, `<br/>`, without attributes) and markdown markup (`**`, `` ` ``, `](`) of the text
+fn markup(text: &str) -> Vec<String> {
+    let mut result = vec![];
+
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let tag = &rest[start + 1..];
+        let end = tag.find(['>', '<']).unwrap_or(tag.len());
+        let is_tag = tag[..end].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/')
+            && tag[end..].starts_with('>');
+        if is_tag {
+            let inner = &tag[..end];
+            let closing = inner.starts_with('/');
+            let self_closing = inner.ends_with('/');
+            let name: String = inner
+                .trim_start_matches('/')
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+                .collect::<String>()
+                .to_ascii_lowercase();
+            result.push(match (closing, self_closing) {
+                (true, _) => format!("</{}>", name),
+                (_, true) => format!("<{}/>", name),
+                _ => format!("<{}>", name),
+            });
+        }
+        rest = &rest[start + 1..];
+    }
+
+    for token in ["**", "__", "`", "]("] {
+        let count = text.matches(token).count();
+        result.extend(std::iter::repeat_n(token.to_string(), count));
+    }
+
+    result
+}
+
+/// The first quote of the text, that has no counterpart
+fn unbalanced_quotes(text: &str) -> Option<&'static str> {
+    if !text.matches('"').count().is_multiple_of(2) {
+        return Some("\"");
+    }
+    for (open, close) in [("«", "»"), ("「", "」"), ("『", "』")] {
+        if text.matches(open).count() != text.matches(close).count() {
+            return Some(open);
+        }
+    }
+    // `„` opens German quotes, that are closed by `“`
+    if !text.contains('„') && text.matches('“').count() != text.matches('”').count() {
+        return Some("“");
+    }
+    None
+}
+
+/// Punctuation at the end of the text, full-width punctuation as its ASCII counterpart
+fn end_punctuation(text: &str) -> Option<char> {
+    let c = text.trim_end().chars().last()?;
+    let c = match c {
+        '。' | '．' => '.',
+        '！' => '!',
+        '？' => '?',
+        '：' => ':',
+        '；' => ';',
+        '，' => ',',
+        '⋯' => '…',
+        c => c,
+    };
+    ['.', '!', '?', ':', ';', ',', '…']
+        .contains(&c)
+        .then_some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(rule: &str, source: &str, text: &str) -> Vec<String> {
+        check(rule, source, text)
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(
+            messages(
+                "placeholders",
+                "Hello %{name}, %{count}",
+                "Bonjour %{nom}, %{count}"
+            ),
+            [
+                "missing placeholder `%{name}`",
+                "unknown placeholder `%{nom}`"
+            ]
+        );
+        assert!(messages("placeholders", "%{a} %{b}", "%{b} %{a}").is_empty());
+
+        assert_eq!(
+            messages("markup", "Click <b>here</b>", "Cliquez <b>ici"),
+            ["missing markup `</b>`"]
+        );
+        assert_eq!(
+            messages("markup", "See [docs](url)", "Voir **docs**"),
+            [
+                "missing markup `](`",
+                "extra markup `**`",
+                "extra markup `**`"
+            ]
+        );
+        assert!(messages("markup", "<a href=\"x\">A</a><br/>", "<A>B</a><br />").is_empty());
+        assert!(messages("markup", "1 < 2", "1 < 2 > 0").is_empty());
+
+        assert_eq!(
+            messages("whitespace", "Name: ", " Nom :"),
+            [
+                "leading whitespace differs from the default locale",
+                "trailing whitespace differs from the default locale"
+            ]
+        );
+        assert!(messages("whitespace", " a\n", " b\n").is_empty());
+
+        assert_eq!(
+            messages("quotes", "Say \"hi\"", "Dis « salut"),
+            ["unbalanced quotes `«`"]
+        );
+        assert!(messages("quotes", "Say \"hi", "Dis \"salut").is_empty());
+        assert!(messages("quotes", "Say \"hi\"", "Sag „hallo“").is_empty());
+
+        assert_eq!(
+            messages("untranslated", "Hello", "Hello"),
+            ["same text as the default locale"]
+        );
+        assert!(messages("untranslated", "%{count}", "%{count}").is_empty());
+
+        assert_eq!(
+            messages("punctuation", "Saved.", "Enregistré"),
+            ["ends with no punctuation, but the default locale with `.`"]
+        );
+        assert!(messages("punctuation", "Saved!", "已保存！").is_empty());
+    }
+
+    #[test]
+    fn test_lint() {
+        let trs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<IndexMap<_, _>>()
+        };
+        let translations = Translations::from([
+            (
+                "en".to_string(),
+                trs(&[("hello", "Hello %{name}!"), ("ok", "OK")]),
+            ),
+            (
+                "en-GB".to_string(),
+                trs(&[("hello", "Hello %{name}!"), ("ok", "OK")]),
+            ),
+            (
+                "fr".to_string(),
+                trs(&[("hello", "Bonjour !"), ("ok", "OK"), ("extra", "Extra")]),
+            ),
+        ]);
+
+        let config = IndexMap::from([("untranslated".to_string(), Severity::Off)]);
+        let issues = lint(&translations, "en", &severities(&config).unwrap());
+        assert_eq!(
+            issues,
+            [Issue {
+                rule: "placeholders",
+                severity: Severity::Error,
+                locale: "fr".to_string(),
+                key: "hello".to_string(),
+                message: "missing placeholder `%{name}`".to_string(),
+            }]
+        );
+
+        let issues = lint(&translations, "en", &severities(&IndexMap::new()).unwrap());
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[1].rule, "untranslated");
+        assert_eq!(issues[1].severity, Severity::Warning);
+
+        let config = IndexMap::from([("unknown".to_string(), Severity::Off)]);
+        assert!(severities(&config).is_err());
+    }
+}
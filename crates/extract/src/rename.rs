@@ -0,0 +1,315 @@
+use crate::extractor::{self, ExtractOptions};
+use anyhow::Error;
+use indexmap::IndexMap;
+use rust_i18n_support::CONTEXT_SEPARATOR;
+use std::fs;
+use std::path::Path;
+
+type Translations = IndexMap<String, IndexMap<String, String>>;
+
+/// Rename of a key, or of all keys with a prefix (`views.*` => `pages.*`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRename {
+    from: String,
+    to: String,
+    prefix: bool,
+}
+
+impl KeyRename {
+    /// Rename `from` to `to`, a prefix is renamed if both end with `*`
+    pub fn new(from: &str, to: &str) -> Result<Self, String> {
+        let (from, to, prefix) = match (from.strip_suffix('*'), to.strip_suffix('*')) {
+            (Some(from), Some(to)) => (from, to, true),
+            (None, None) => (from, to, false),
+            _ => {
+                return Err(format!(
+                    "Both keys must end with `*` to rename a prefix, got `{}` and `{}`",
+                    from, to
+                ))
+            }
+        };
+        if from.is_empty() || to.is_empty() {
+            return Err("Keys to rename must not be empty".into());
+        }
+
+        Ok(Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            prefix,
+        })
+    }
+
+    /// New name of the key, `None` if the key is not renamed
+    ///
+    /// Items of a list (`key.0`), variants (`key.%{gender}.female`) and contexts (`key|menu`)
+    /// are renamed together with their key.
+    pub fn rename(&self, key: &str) -> Option<String> {
+        let rest = key.strip_prefix(self.from.as_str())?;
+        let renamed = self.prefix
+            || rest.is_empty()
+            || rest.starts_with(CONTEXT_SEPARATOR)
+            || rest.starts_with(".%{")
+            || rest
+                .strip_prefix('.')
+                .and_then(|item| item.split('.').next())
+                .is_some_and(|index| {
+                    !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit())
+                });
+
+        renamed.then(|| format!("{}{}", self.to, rest))
+    }
+}
+
+/// Rename keys of translations, return the number of renamed translations
+///
+/// Fails without any change, if a new key already exists and is not renamed itself.
+pub fn rename_translations(trs: &mut Translations, rename: &KeyRename) -> Result<usize, String> {
+    for (locale, trs) in trs.iter() {
+        for key in trs.keys() {
+            let Some(new_key) = rename.rename(key) else {
+                continue;
+            };
+            if trs.contains_key(&new_key) && rename.rename(&new_key).is_none() {
+                return Err(format!(
+                    "Can not rename `{}` to `{}`, the key already exists in [{}]",
+                    key, new_key, locale
+                ));
+            }
+        }
+    }
+
+    let mut count = 0;
+    for trs in trs.values_mut() {
+        *trs = trs
+            .drain(..)
+            .map(|(key, value)| match rename.rename(&key) {
+                Some(new_key) => {
+                    count += 1;
+                    (new_key, value)
+                }
+                None => (key, value),
+            })
+            .collect();
+    }
+
+    Ok(count)
+}
+
+/// Rename keys of `t!` calls in the Rust files in `dir` (except `target`),
+/// return the number of renamed calls and changed files
+pub fn rename_in_sources(
+    dir: &Path,
+    rename: &KeyRename,
+    options: &ExtractOptions,
+) -> Result<(usize, usize), Error> {
+    let mut calls = 0;
+    let mut files = 0;
+    for path in extractor::source_files(dir)? {
+        let source = fs::read_to_string(&path)?;
+        let (renamed, count) = rename_in_source(&path, &source, rename, options)?;
+        if count > 0 {
+            fs::write(&path, renamed)?;
+            eprintln!("Renamed {} keys in {}", count, path.display());
+            calls += count;
+            files += 1;
+        }
+    }
+
+    Ok((calls, files))
+}
+
+/// Rename keys of `t!` calls in the source, at the locations of keys found by the extractor,
+/// return the new source and the number of renamed calls
+pub fn rename_in_source(
+    path: &Path,
+    source: &str,
+    rename: &KeyRename,
+    options: &ExtractOptions,
+) -> Result<(String, usize), Error> {
+    if syn::parse_file(source).is_err() {
+        eprintln!("Skipping {}, failed to parse it", path.display());
+        return Ok((source.to_string(), 0));
+    }
+
+    let mut results = IndexMap::new();
+    extractor::extract(&mut results, &path.to_path_buf(), source, options)?;
+    extractor::extract_macros(&mut results, &path.to_path_buf(), source, options)?;
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut edits = vec![];
+    for message in results.values() {
+        let Some(new_key) = rename.rename(message.source_key()) else {
+            continue;
+        };
+        for location in &message.locations {
+            let Some(line_start) = line_starts.get(location.line - 1) else {
+                continue;
+            };
+            let Some((offset, _)) = source[*line_start..].char_indices().nth(location.column)
+            else {
+                continue;
+            };
+            let start = line_start + offset;
+            if let Some(len) = string_literal_len(&source[start..]) {
+                edits.push((start, start + len, format!("{:?}", new_key)));
+            }
+        }
+    }
+
+    // Replace from the end, so offsets of the former edits stay valid
+    edits.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+    edits.dedup_by_key(|(start, _, _)| *start);
+    let mut result = source.to_string();
+    for (start, end, literal) in &edits {
+        result.replace_range(start..end, literal);
+    }
+
+    Ok((result, edits.len()))
+}
+
+/// Length of the string literal (`"..."` or `r#"..."#`) at the start of `source`
+fn string_literal_len(source: &str) -> Option<usize> {
+    if let Some(raw) = source.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let body = raw[hashes..].strip_prefix('"')?;
+        let end = "\"".to_string() + &"#".repeat(hashes);
+        return Some(1 + hashes + 1 + body.find(&end)? + end.len());
+    }
+
+    let mut chars = source.strip_prefix('"')?.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return Some(i + 2),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_rename() {
+        let rename = KeyRename::new("hello", "greeting").unwrap();
+        assert_eq!(rename.rename("hello").as_deref(), Some("greeting"));
+        assert_eq!(rename.rename("hello.0").as_deref(), Some("greeting.0"));
+        assert_eq!(
+            rename.rename("hello.2.title").as_deref(),
+            Some("greeting.2.title")
+        );
+        assert_eq!(
+            rename.rename("hello.%{gender}.female").as_deref(),
+            Some("greeting.%{gender}.female")
+        );
+        assert_eq!(
+            rename.rename("hello|menu").as_deref(),
+            Some("greeting|menu")
+        );
+        assert_eq!(rename.rename("hello.world"), None);
+        assert_eq!(rename.rename("hello_world"), None);
+
+        let rename = KeyRename::new("views.*", "pages.*").unwrap();
+        assert_eq!(
+            rename.rename("views.home.title").as_deref(),
+            Some("pages.home.title")
+        );
+        assert_eq!(rename.rename("view.title"), None);
+
+        assert!(KeyRename::new("views.*", "pages").is_err());
+        assert!(KeyRename::new("*", "pages.*").is_err());
+        assert!(KeyRename::new("", "hello").is_err());
+    }
+
+    #[test]
+    fn test_rename_translations() {
+        let trs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<IndexMap<_, _>>()
+        };
+        let mut translations = Translations::from([
+            (
+                "en".to_string(),
+                trs(&[
+                    ("a", "A"),
+                    ("views.title", "Title"),
+                    ("views.days.0", "Mon"),
+                ]),
+            ),
+            ("fr".to_string(), trs(&[("views.title", "Titre")])),
+        ]);
+
+        let rename = KeyRename::new("views.*", "pages.*").unwrap();
+        assert_eq!(rename_translations(&mut translations, &rename), Ok(3));
+        assert_eq!(
+            translations["en"].keys().collect::<Vec<_>>(),
+            ["a", "pages.title", "pages.days.0"]
+        );
+        assert_eq!(translations["fr"]["pages.title"], "Titre");
+
+        let rename = KeyRename::new("a", "pages.title").unwrap();
+        assert!(rename_translations(&mut translations, &rename).is_err());
+        assert_eq!(translations["en"]["a"], "A");
+    }
+
+    #[test]
+    fn test_rename_in_source() {
+        let source = r###"
+fn main() {
+    println!("{}", t!("views.title"));
+    let _ = t!(r#"views.desc"#, locale = "de", name = "views.title");
+    let _ = t!("Open", context = "menu");
+    let _ = t!("é views.title");
+    let _ = t!("é"); let _ = t!("views.title");
+    let _ = tr!("views.title"); let _ = label(42, "views.title");
+}
+"###;
+        let options = ExtractOptions {
+            macros: vec!["tr".parse().unwrap()],
+            functions: vec!["label:1".parse().unwrap()],
+        };
+        let rename = KeyRename::new("views.*", "pages.*").unwrap();
+        let (result, count) =
+            rename_in_source(Path::new("main.rs"), source, &rename, &options).unwrap();
+        assert_eq!(count, 5);
+        assert_eq!(
+            result,
+            r###"
+fn main() {
+    println!("{}", t!("pages.title"));
+    let _ = t!("pages.desc", locale = "de", name = "views.title");
+    let _ = t!("Open", context = "menu");
+    let _ = t!("é views.title");
+    let _ = t!("é"); let _ = t!("pages.title");
+    let _ = tr!("pages.title"); let _ = label(42, "pages.title");
+}
+"###
+        );
+
+        let rename = KeyRename::new("Open", "Open file").unwrap();
+        let (result, count) =
+            rename_in_source(Path::new("main.rs"), source, &rename, &options).unwrap();
+        assert_eq!(count, 1);
+        assert!(result.contains(r#"t!("Open file", context = "menu")"#));
+
+        let (result, count) =
+            rename_in_source(Path::new("main.rs"), "fn main( {", &rename, &options).unwrap();
+        assert_eq!((result.as_str(), count), ("fn main( {", 0));
+    }
+
+    #[test]
+    fn test_string_literal_len() {
+        assert_eq!(string_literal_len(r#""abc", x"#), Some(5));
+        assert_eq!(string_literal_len(r#""a\"b\\", x"#), Some(8));
+        assert_eq!(string_literal_len(r###"r#"a"b"#, x"###), Some(8));
+        assert_eq!(string_literal_len("abc"), None);
+    }
+}
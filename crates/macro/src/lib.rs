@@ -1,19 +1,45 @@
 use indexmap::IndexMap;
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
-use rust_i18n_support::{is_debug, load_locales};
-use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Ident, Lit, LitStr, Token};
+use rust_i18n_support::{
+    config, encode_compact, encode_translations, icu::MessageFormat, is_debug,
+    load_locales_with_meta, locale::canonicalize_locale, LoadOptions, LocaleLayout,
+};
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Ident, Lit, LitBool, LitStr, Token};
 
 struct Args {
-    locales_path: String,
+    /// Load paths, translations of the first path take precedence
+    locales_paths: Vec<String>,
+    default_locale: String,
     fallback: Option<String>,
     extend: Option<Expr>,
+    namespaced: bool,
+    layout: LocaleLayout,
+    lazy: bool,
+    compact: bool,
+    compress: bool,
+    locales: Option<Vec<String>>,
+    locale_features: bool,
+    icu: bool,
+    canonicalize_locales: bool,
 }
 
 impl Args {
     fn consume_path(&mut self, input: syn::parse::ParseStream) -> syn::parse::Result<()> {
         let path = input.parse::<LitStr>()?;
-        self.locales_path = path.value();
+        self.locales_paths = vec![path.value()];
+
+        Ok(())
+    }
+
+    fn consume_paths(&mut self, input: syn::parse::ParseStream) -> syn::parse::Result<()> {
+        let content;
+        let brackets = syn::bracketed!(content in input);
+        let paths = content.parse_terminated(|input| input.parse::<LitStr>(), Token![,])?;
+        if paths.is_empty() {
+            return Err(syn::Error::new(brackets.span.join(), "Expected load paths"));
+        }
+        self.locales_paths = paths.iter().map(|path| path.value()).collect();
 
         Ok(())
     }
@@ -31,6 +57,57 @@ impl Args {
                 let val = input.parse::<Expr>()?;
                 self.extend = Some(val);
             }
+            "namespaced" => {
+                let val = input.parse::<LitBool>()?.value;
+                self.namespaced = val;
+            }
+            "lazy" => {
+                let val = input.parse::<LitBool>()?.value;
+                self.lazy = val;
+            }
+            "compact" => {
+                let val = input.parse::<LitBool>()?.value;
+                self.compact = val;
+            }
+            "icu" => {
+                let val = input.parse::<LitBool>()?.value;
+                self.icu = val;
+            }
+            "canonicalize_locales" => {
+                let val = input.parse::<LitBool>()?.value;
+                self.canonicalize_locales = val;
+            }
+            "compress" => {
+                let val = input.parse::<LitBool>()?;
+                if val.value && !cfg!(feature = "compress") {
+                    return Err(syn::Error::new(
+                        val.span(),
+                        "`compress` requires the `compress` feature of rust-i18n",
+                    ));
+                }
+                self.compress = val.value;
+            }
+            "locales" => {
+                let content;
+                syn::bracketed!(content in input);
+                let locales = content
+                    .parse_terminated(|input| input.parse::<LitStr>(), Token![,])?
+                    .iter()
+                    .map(|lit| lit.value())
+                    .collect();
+                self.locales = Some(locales);
+            }
+            "locale_features" => {
+                let val = input.parse::<LitBool>()?.value;
+                self.locale_features = val;
+            }
+            "layout" => {
+                let val = input.parse::<LitStr>()?;
+                self.layout = val
+                    .value()
+                    .parse()
+                    .map_err(|e: String| syn::Error::new(val.span(), e))?;
+            }
             _ => {}
         }
 
@@ -43,6 +120,29 @@ impl Args {
     }
 }
 
+impl Args {
+    /// Validate the locales of the arguments, and canonicalize them if `canonicalize_locales` is enabled
+    fn check_locales(&mut self) -> Result<(), String> {
+        let canonicalize = self.canonicalize_locales;
+        let check = |locale: &mut String| -> Result<(), String> {
+            let canonical = canonicalize_locale(locale)?;
+            if canonicalize {
+                *locale = canonical;
+            }
+            Ok(())
+        };
+
+        check(&mut self.default_locale)?;
+        if let Some(fallback) = self.fallback.as_mut() {
+            check(fallback)?;
+        }
+        if let Some(locales) = self.locales.as_mut() {
+            locales.iter_mut().try_for_each(check)?;
+        }
+        Ok(())
+    }
+}
+
 impl syn::parse::Parse for Args {
     /// Parse macro arguments.
     ///
@@ -50,20 +150,42 @@ impl syn::parse::Parse for Args {
     /// i18n!();
     /// i18n!("locales");
     /// i18n!("locales", fallback = "en");
+    /// i18n!(["locales", "../shared/locales"]);
     /// ```
     ///
     /// Ref: https://docs.rs/syn/latest/syn/parse/index.html
     fn parse(input: syn::parse::ParseStream) -> syn::parse::Result<Self> {
         let lookahead = input.lookahead1();
 
+        // Defaults are taken from the I18n config in Cargo.toml
+        let cfg = config::load(&cargo_dir()).map_err(|e| {
+            syn::Error::new(Span::call_site(), format!("Invalid I18n config, {}", e))
+        })?;
+
         let mut result = Self {
-            locales_path: String::from("locales"),
-            fallback: None,
+            locales_paths: vec![cfg.load_path.clone()],
+            default_locale: cfg.default_locale.clone(),
+            fallback: cfg.fallback.clone(),
             extend: None,
+            namespaced: cfg.namespaced,
+            layout: cfg.layout,
+            lazy: false,
+            compact: false,
+            compress: false,
+            locales: cfg
+                .compile_available_locales
+                .then_some(cfg.available_locales),
+            locale_features: false,
+            icu: false,
+            canonicalize_locales: cfg.canonicalize_locales,
         };
 
-        if lookahead.peek(LitStr) {
-            result.consume_path(input)?;
+        if lookahead.peek(LitStr) || lookahead.peek(syn::token::Bracket) {
+            if lookahead.peek(LitStr) {
+                result.consume_path(input)?;
+            } else {
+                result.consume_paths(input)?;
+            }
 
             if input.parse::<Token![,]>().is_ok() {
                 result.consume_options(input)?;
@@ -72,6 +194,10 @@ impl syn::parse::Parse for Args {
             result.consume_options(input)?;
         }
 
+        result
+            .check_locales()
+            .map_err(|e| syn::Error::new(Span::call_site(), e))?;
+
         Ok(result)
     }
 }
@@ -80,24 +206,109 @@ impl syn::parse::Parse for Args {
 ///
 /// This will load all translations by glob `**/*.yml` from the given path, default: `${CARGO_MANIFEST_DIR}/locales`.
 ///
+/// Multiple paths can be given as a list, translations of a former path override the ones of a latter path.
+///
+/// Defaults of the load path and attributes are taken from the `[package.metadata.i18n]` config in `Cargo.toml`
+/// (`load-path`, `fallback`, `namespaced`, `layout`, and `available-locales` with `compile-available-locales = true`),
+/// the arguments of the macro take precedence.
+/// The `default-locale` is returned by `rust_i18n::default_locale!()`.
+///
 /// Attribute `fallback` for set the fallback locale, if present `t` macro will use it as the fallback locale.
 ///
+/// Attribute `layout` for take locale from the first directory (`"directory"`: `locales/en/view.yml`)
+/// instead of the file name (`"file"`, default: `locales/view.en.yml`).
+///
+/// Attribute `namespaced` for prefix keys with the namespace of their file, e.g. `title` in `view.en.yml` => `view.title`.
+///
+/// Attribute `lazy` for embed each locale as a compact blob, that is decoded on the first use of the locale
+/// and can be freed again by `rust_i18n::unload_locale!`.
+///
+/// Attribute `compact` for store every key only once and deduplicate values of a locale (implies `lazy`),
+/// `compress` also compresses the tables of each locale (requires the `compress` feature).
+/// Run with `RUST_I18N_DEBUG=1` to print the size of embedded data.
+///
+/// Attribute `locales` for compile in only the listed locales, by default `available-locales` of the
+/// I18n config in `Cargo.toml` if `compile-available-locales = true`, otherwise all locales of the files.
+/// The `RUST_I18N_LOCALES` env var (e.g. `RUST_I18N_LOCALES=en,fr`) overrides it at build time.
+/// The fallback locale is always included.
+///
+/// Attribute `locale_features` for include a locale only if the `locale-<locale>` feature
+/// (e.g. `locale-zh-CN`) of the crate is enabled, the fallback locale is always included.
+///
+/// Locales of files, of the I18n config and of the attributes must be valid BCP-47 tags (`en`, `zh-CN`, `zh-Hant-TW`).
+/// Attribute `canonicalize_locales` (or `canonicalize-locales` of the config) for map them to their canonical form,
+/// e.g. translations of `zh_CN.yml` are loaded as `zh-CN`.
+///
+/// Attribute `icu` for interpret translations as ICU MessageFormat (`{count, plural, one {# file} other {# files}}`),
+/// with the variables of `t!` as arguments, messages are parsed on the first use and cached.
+///
 /// ```ignore
 /// i18n!();
 /// i18n!("locales");
 /// i18n!("locales", fallback = "en");
+/// i18n!(["locales", "../shared/locales"], fallback = "en");
+/// i18n!("locales", namespaced = true);
+/// i18n!("locales", layout = "directory");
+/// i18n!("locales", lazy = true);
+/// i18n!("locales", compact = true);
+/// i18n!("locales", compress = true);
+/// i18n!("locales", locales = ["en", "fr"]);
+/// i18n!("locales", fallback = "en", locale_features = true);
+/// i18n!("locales", icu = true);
+/// i18n!("locales", canonicalize_locales = true);
 /// ```
 #[proc_macro]
 pub fn i18n(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let args = parse_macro_input!(input as Args);
 
-    // CARGO_MANIFEST_DIR is current build directory
-    let cargo_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is empty");
-    let current_dir = std::path::PathBuf::from(cargo_dir);
-    let locales_path = current_dir.join(&args.locales_path);
+    let options = LoadOptions {
+        namespaced: args.namespaced,
+        layout: args.layout,
+        canonicalize_locales: args.canonicalize_locales,
+        // Suggestions of `cargo i18n` are not translations until they are reviewed
+        skip_unreviewed: true,
+    };
+
+    // Load the lowest precedence path first, so it can be overridden by the following ones
+    let mut data: IndexMap<String, IndexMap<String, String>> = IndexMap::new();
+    let mut meta: IndexMap<String, IndexMap<String, String>> = IndexMap::new();
+    for locales_path in args.locales_paths.iter().rev() {
+        let locales_path = cargo_dir().join(locales_path);
+        let (trs, locales_meta) =
+            load_locales_with_meta(&locales_path.display().to_string(), |_| false, &options);
+        for (locale, trs) in trs {
+            data.entry(locale).or_default().extend(trs);
+        }
+        for (locale, locale_meta) in locales_meta {
+            meta.entry(locale).or_default().extend(locale_meta);
+        }
+    }
+    if let Some(locales) = selected_locales(&args) {
+        data.retain(|locale, _| locales.contains(locale) || args.fallback.as_ref() == Some(locale));
+        meta.retain(|locale, _| data.contains_key(locale));
+    }
+
+    if args.icu {
+        if let Err(err) = check_icu_messages(&data) {
+            return err.to_compile_error().into();
+        }
+    }
+
+    // Rebuild when the I18n config is changed
+    let manifests = match config::manifest_files(&cargo_dir()) {
+        Ok(files) => files.into_iter().map(|file| file.display().to_string()),
+        Err(e) => {
+            let message = format!("Invalid I18n config, {}", e);
+            return syn::Error::new(Span::call_site(), message)
+                .to_compile_error()
+                .into();
+        }
+    };
 
-    let data = load_locales(&locales_path.display().to_string(), |_| false);
-    let code = generate_code(data, args);
+    let mut code = generate_code(data, meta, args);
+    code.extend(quote! {
+        #(const _: &[u8] = include_bytes!(#manifests);)*
+    });
 
     if is_debug() {
         println!(
@@ -109,28 +320,70 @@ pub fn i18n(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     code.into()
 }
 
+/// Check that all translations are valid ICU messages, so an invalid one is not shown as it is at runtime
+fn check_icu_messages(data: &IndexMap<String, IndexMap<String, String>>) -> syn::Result<()> {
+    for (locale, trs) in data {
+        for (key, value) in trs {
+            if let Err(err) = MessageFormat::parse(value) {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("Invalid ICU message of `{}` in [{}], {}", key, locale, err),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// CARGO_MANIFEST_DIR is current build directory
+fn cargo_dir() -> std::path::PathBuf {
+    let cargo_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is empty");
+    std::path::PathBuf::from(cargo_dir)
+}
+
+/// Locales to compile in, `None` for all of them.
+fn selected_locales(args: &Args) -> Option<Vec<String>> {
+    if let Ok(locales) = std::env::var("RUST_I18N_LOCALES") {
+        return Some(
+            locales
+                .split(',')
+                .map(|locale| locale.trim())
+                .filter(|locale| !locale.is_empty())
+                .map(|locale| match canonicalize_locale(locale) {
+                    Ok(canonical) if args.canonicalize_locales => canonical,
+                    _ => locale.to_string(),
+                })
+                .collect(),
+        );
+    }
+
+    args.locales.clone()
+}
+
+/// `#[cfg(feature = "locale-<locale>")]` for the locale, if `locale_features` is enabled.
+fn locale_cfg(args: &Args, locale: &str) -> proc_macro2::TokenStream {
+    if !args.locale_features || args.fallback.as_deref() == Some(locale) {
+        return quote! {};
+    }
+
+    let feature = format!("locale-{}", locale);
+    quote! {
+        #[cfg(feature = #feature)]
+    }
+}
+
 fn generate_code(
     translations: IndexMap<String, IndexMap<String, String>>,
+    meta: IndexMap<String, IndexMap<String, String>>,
     args: Args,
 ) -> proc_macro2::TokenStream {
-    let mut all_translations = Vec::<proc_macro2::TokenStream>::new();
-
-    translations.iter().for_each(|(locale, trs)| {
-        let mut sub_trs = Vec::<proc_macro2::TokenStream>::new();
-
-        trs.iter().for_each(|(k, v)| {
-            let k = k.to_string();
-            let v = v.to_string();
-            sub_trs.push(quote! {
-                (#k, #v)
-            });
-        });
-
-        all_translations.push(quote! {
-            let trs = [#(#sub_trs),*];
-            backend.add_translations(#locale, &trs.into_iter().collect());
-        });
-    });
+    let backend_code = if args.compact || args.compress {
+        generate_compact_backend(&translations, &args)
+    } else if args.lazy {
+        generate_lazy_backend(&translations, &args)
+    } else {
+        generate_simple_backend(&translations, &args)
+    };
 
     let fallback = if let Some(fallback) = args.fallback {
         quote! {
@@ -142,6 +395,8 @@ fn generate_code(
         }
     };
 
+    let default_locale = &args.default_locale;
+
     let extend_code = if let Some(extend) = args.extend {
         quote! {
             let backend = backend.extend(#extend);
@@ -150,17 +405,46 @@ fn generate_code(
         quote! {}
     };
 
+    let meta = meta.iter().map(|(locale, locale_meta)| {
+        let keys = locale_meta.keys();
+        let values = locale_meta.values();
+        quote! {
+            (#locale, &[#((#keys, #values)),*])
+        }
+    });
+
+    // Keys with variants, e.g. `invite` of `invite.%{gender}.female`, sorted for binary search
+    let select_keys = translations
+        .values()
+        .flat_map(|trs| trs.keys())
+        .filter_map(|key| key.split_once(".%{").map(|(key, _)| key))
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter();
+
+    let format_code = if args.icu {
+        quote! {
+            rust_i18n::icu::format_cached(locale, value, args)
+        }
+    } else {
+        quote! {
+            let _ = (locale, args);
+            value.to_string()
+        }
+    };
+
     // result
     quote! {
         use rust_i18n::BackendExt;
 
+        // Rebuild when the locales selected at build time are changed
+        const _: Option<&str> = option_env!("RUST_I18N_LOCALES");
+
         /// I18n backend instance
         ///
         /// [PUBLIC] This is a public API, and as an example in examples/
         #[allow(missing_docs)]
         static _RUST_I18N_BACKEND: rust_i18n::once_cell::sync::Lazy<Box<dyn rust_i18n::Backend>> = rust_i18n::once_cell::sync::Lazy::new(|| {
-            let mut backend = rust_i18n::SimpleBackend::new();
-            #(#all_translations)*
+            #backend_code
             #extend_code
 
             Box::new(backend)
@@ -168,18 +452,37 @@ fn generate_code(
 
         static _RUST_I18N_FALLBACK_LOCALE: Option<&'static str> = #fallback;
 
+        /// Keys with variants selected by arguments, sorted
+        static _RUST_I18N_SELECT_KEYS: &[&str] = &[#(#select_keys),*];
+
+        /// `_meta` sections of locale files
+        static _RUST_I18N_LOCALE_META: &[(&str, &[(&str, &str)])] = &[#(#meta),*];
+
+        /// Get I18n text from backends registered for this crate or from its own backend
+        #[inline]
+        fn _rust_i18n_lookup(locale: &str, key: &str) -> Option<&'static str> {
+            rust_i18n::translate_registered(env!("CARGO_PKG_NAME"), locale, key)
+                .or_else(|| _RUST_I18N_BACKEND.translate(locale, key))
+        }
+
+        /// Format a translation with the arguments, as ICU MessageFormat if `icu` is enabled
+        #[inline]
+        fn _rust_i18n_format(locale: &str, value: &'static str, args: &[(&str, rust_i18n::icu::Argument)]) -> String {
+            #format_code
+        }
+
         /// Get I18n text by locale and key
         #[inline]
         #[allow(missing_docs)]
         pub fn _rust_i18n_translate(locale: &str, key: &str) -> String {
-            if let Some(value) = _RUST_I18N_BACKEND.translate(locale, key) {
-                return value.to_string();
+            if let Some(value) = _rust_i18n_lookup(locale, key) {
+                return _rust_i18n_format(locale, value, &[]);
             }
 
 
             if let Some(fallback) = _RUST_I18N_FALLBACK_LOCALE {
-                if let Some(value) = _RUST_I18N_BACKEND.translate(fallback, key) {
-                    return value.to_string();
+                if let Some(value) = _rust_i18n_lookup(fallback, key) {
+                    return _rust_i18n_format(fallback, value, &[]);
                 }
             }
 
@@ -189,12 +492,255 @@ fn generate_code(
             return format!("{}.{}", locale, key);
         }
 
+        /// Get I18n text by locale, key and context, if there is no translation with the context, same as `_rust_i18n_translate`
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_translate_with_context(locale: &str, key: &str, context: &str) -> String {
+            let context_key = rust_i18n::context_key(key, context);
+            if let Some(value) = _rust_i18n_lookup(locale, &context_key) {
+                return _rust_i18n_format(locale, value, &[]);
+            }
+
+            if let Some(fallback) = _RUST_I18N_FALLBACK_LOCALE {
+                if let Some(value) = _rust_i18n_lookup(fallback, &context_key) {
+                    return _rust_i18n_format(fallback, value, &[]);
+                }
+            }
+
+            _rust_i18n_translate(locale, key)
+        }
+
+        /// Get I18n text by locale and key, with the variant selected by the arguments if the key has variants
+        #[inline]
+        fn _rust_i18n_lookup_select(locale: &str, key: &str, args: &[(&str, rust_i18n::icu::Argument)]) -> Option<&'static str> {
+            // Only keys with variants are looked up by the arguments, others are translated as they are
+            let has_variants = _RUST_I18N_SELECT_KEYS.binary_search(&key).is_ok();
+            args.iter()
+                .filter(|_| has_variants)
+                .find_map(|(name, value)| _rust_i18n_lookup(locale, &rust_i18n::variant_key(key, name, &value.to_string())))
+                .or_else(|| _rust_i18n_lookup(locale, key))
+        }
+
+        /// Get I18n text by locale and key, with the variant selected by the arguments, e.g. `key.%{gender}.female` for `gender = "female"`
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_translate_select(locale: &str, key: &str, args: &[(&str, rust_i18n::icu::Argument)]) -> String {
+            if let Some(value) = _rust_i18n_lookup_select(locale, key, args) {
+                return _rust_i18n_format(locale, value, args);
+            }
+
+            if let Some(fallback) = _RUST_I18N_FALLBACK_LOCALE {
+                if let Some(value) = _rust_i18n_lookup_select(fallback, key, args) {
+                    return _rust_i18n_format(fallback, value, args);
+                }
+            }
+
+            _rust_i18n_translate(locale, key)
+        }
+
+        /// Get I18n text by locale, key and context, with the variant selected by the arguments,
+        /// if there is no translation with the context, same as `_rust_i18n_translate_select`
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_translate_select_with_context(locale: &str, key: &str, context: &str, args: &[(&str, rust_i18n::icu::Argument)]) -> String {
+            let context_key = rust_i18n::context_key(key, context);
+            if let Some(value) = _rust_i18n_lookup_select(locale, &context_key, args) {
+                return _rust_i18n_format(locale, value, args);
+            }
+
+            if let Some(fallback) = _RUST_I18N_FALLBACK_LOCALE {
+                if let Some(value) = _rust_i18n_lookup_select(fallback, &context_key, args) {
+                    return _rust_i18n_format(fallback, value, args);
+                }
+            }
+
+            _rust_i18n_translate_select(locale, key, args)
+        }
+
+        /// Get I18n texts of a list (`key.0`, `key.1`, ...) by locale and key
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_translate_list(locale: &str, key: &str) -> Vec<String> {
+            let items = |locale: &str| {
+                (0..)
+                    .map_while(|i| _rust_i18n_lookup(locale, &format!("{}.{}", key, i)))
+                    .map(|value| _rust_i18n_format(locale, value, &[]))
+                    .collect::<Vec<_>>()
+            };
+
+            let list = items(locale);
+            if list.is_empty() {
+                if let Some(fallback) = _RUST_I18N_FALLBACK_LOCALE {
+                    return items(fallback);
+                }
+            }
+            list
+        }
+
         #[allow(missing_docs)]
         pub fn _rust_i18n_available_locales() -> Vec<&'static str> {
             let mut locales = _RUST_I18N_BACKEND.available_locales();
+            for locale in rust_i18n::registered_locales(env!("CARGO_PKG_NAME")) {
+                if !locales.contains(&locale) {
+                    locales.push(locale);
+                }
+            }
             locales.sort();
             locales
         }
+
+        /// Get the backend with translations of this crate, e.g. for register it to another crate
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_backend() -> &'static dyn rust_i18n::Backend {
+            &**_RUST_I18N_BACKEND
+        }
+
+        /// Get the default locale of the I18n config
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_default_locale() -> &'static str {
+            #default_locale
+        }
+
+        /// Get the metadata of the locale, from backends, `_meta` of locale files and built-in defaults
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_locale_info(locale: &str) -> rust_i18n::LocaleInfo {
+            if let Some(info) = rust_i18n::registered_locale_info(env!("CARGO_PKG_NAME"), locale) {
+                return info;
+            }
+            if let Some(info) = _RUST_I18N_BACKEND.locale_info(locale) {
+                return info;
+            }
+
+            let meta = _RUST_I18N_LOCALE_META
+                .iter()
+                .find(|(meta_locale, _)| *meta_locale == locale)
+                .map(|(_, meta)| *meta)
+                .unwrap_or_default();
+            rust_i18n::LocaleInfo::new(locale).with_meta(meta.iter().copied())
+        }
+
+        /// Free memory used by translations of the locale (only for `lazy` backend)
+        #[allow(missing_docs)]
+        pub fn _rust_i18n_unload_locale(locale: &str) {
+            _RUST_I18N_BACKEND.unload_locale(locale);
+        }
+    }
+}
+
+fn generate_simple_backend(
+    translations: &IndexMap<String, IndexMap<String, String>>,
+    args: &Args,
+) -> proc_macro2::TokenStream {
+    let mut all_translations = Vec::<proc_macro2::TokenStream>::new();
+
+    translations.iter().for_each(|(locale, trs)| {
+        let mut sub_trs = Vec::<proc_macro2::TokenStream>::new();
+
+        trs.iter().for_each(|(k, v)| {
+            let k = k.to_string();
+            let v = v.to_string();
+            sub_trs.push(quote! {
+                (#k, #v)
+            });
+        });
+
+        let cfg = locale_cfg(args, locale);
+        all_translations.push(quote! {
+            #cfg
+            {
+                let trs = [#(#sub_trs),*];
+                backend.add_translations(#locale, &trs.into_iter().collect());
+            }
+        });
+    });
+
+    quote! {
+        let mut backend = rust_i18n::SimpleBackend::new();
+        #(#all_translations)*
+    }
+}
+
+fn generate_lazy_backend(
+    translations: &IndexMap<String, IndexMap<String, String>>,
+    args: &Args,
+) -> proc_macro2::TokenStream {
+    let blobs = translations.iter().map(|(locale, trs)| {
+        let blob = Literal::byte_string(&encode_translations(trs));
+        let cfg = locale_cfg(args, locale);
+        quote! {
+            #cfg
+            (#locale, #blob)
+        }
+    });
+
+    quote! {
+        let backend = rust_i18n::LazyBackend::new(&[#(#blobs),*]);
+    }
+}
+
+fn generate_compact_backend(
+    translations: &IndexMap<String, IndexMap<String, String>>,
+    args: &Args,
+) -> proc_macro2::TokenStream {
+    // With `locale_features`, a locale behind a feature has its own key table, so the keys of
+    // a locale that is not compiled in are not compiled in either
+    let (always, gated): (IndexMap<_, _>, IndexMap<_, _>) = translations
+        .clone()
+        .into_iter()
+        .partition(|(locale, _)| locale_cfg(args, locale).is_empty());
+
+    let backend = compact_backend(&always, args.compress);
+    let mut code = quote! {
+        let backend = #backend;
+    };
+    for (locale, trs) in gated {
+        let cfg = locale_cfg(args, &locale);
+        let backend = compact_backend(&IndexMap::from([(locale, trs)]), args.compress);
+        code.extend(quote! {
+            #cfg
+            let backend = backend.extend(#backend);
+        });
+    }
+    code
+}
+
+/// `LazyBackend` with the translations in one key table and a table for each locale
+fn compact_backend(
+    translations: &IndexMap<String, IndexMap<String, String>>,
+    compress: bool,
+) -> proc_macro2::TokenStream {
+    let data = encode_compact(translations, compress);
+
+    if is_debug() {
+        let plain_size: usize = translations
+            .values()
+            .flat_map(|trs| trs.iter())
+            .map(|(k, v)| k.len() + v.len())
+            .sum();
+        println!(
+            "cargo:i18n-size=plain: {} bytes, {}: {} bytes (keys: {} bytes)",
+            plain_size,
+            if compress { "compressed" } else { "compact" },
+            data.size(),
+            data.keys.len()
+        );
+        for (locale, table) in &data.locales {
+            println!("cargo:i18n-size={}: {} bytes", locale, table.len());
+        }
+    }
+
+    let keys = Literal::byte_string(&data.keys);
+    let blobs = data.locales.iter().map(|(locale, table)| {
+        let table = Literal::byte_string(table);
+        quote! {
+            (#locale, #table)
+        }
+    });
+
+    let constructor = if compress {
+        quote! { compressed }
+    } else {
+        quote! { compact }
+    };
+
+    quote! {
+        rust_i18n::LazyBackend::#constructor(#keys, &[#(#blobs),*])
     }
 }
 
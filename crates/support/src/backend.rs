@@ -1,3 +1,4 @@
+use crate::LocaleInfo;
 use indexmap::map::IndexMap;
 
 /// I18n backend trait
@@ -6,6 +7,30 @@ pub trait Backend: Send + Sync + 'static {
     fn available_locales(&self) -> Vec<&str>;
     /// Get the translation for the given locale and key
     fn translate(&self, locale: &str, key: &str) -> Option<&str>;
+    /// Free memory used by translations of the given locale, if backend supports it
+    fn unload_locale(&self, _locale: &str) {}
+    /// Get the metadata (direction, names, plural rules) of the given locale, if backend has it
+    fn locale_info(&self, _locale: &str) -> Option<LocaleInfo> {
+        None
+    }
+}
+
+impl Backend for &'static dyn Backend {
+    fn available_locales(&self) -> Vec<&str> {
+        (**self).available_locales()
+    }
+
+    fn translate(&self, locale: &str, key: &str) -> Option<&str> {
+        (**self).translate(locale, key)
+    }
+
+    fn unload_locale(&self, locale: &str) {
+        (**self).unload_locale(locale)
+    }
+
+    fn locale_info(&self, locale: &str) -> Option<LocaleInfo> {
+        (**self).locale_info(locale)
+    }
 }
 
 pub trait BackendExt: Backend {
@@ -41,12 +66,24 @@ where
             .translate(locale, key)
             .or_else(|| self.0.translate(locale, key))
     }
+
+    fn unload_locale(&self, locale: &str) {
+        self.0.unload_locale(locale);
+        self.1.unload_locale(locale);
+    }
+
+    fn locale_info(&self, locale: &str) -> Option<LocaleInfo> {
+        self.1
+            .locale_info(locale)
+            .or_else(|| self.0.locale_info(locale))
+    }
 }
 
 /// Simple KeyValue storage backend
 pub struct SimpleBackend {
     /// All translations key is flatten key, like `en.hello.world`
     translations: IndexMap<String, IndexMap<String, String>>,
+    locale_infos: IndexMap<String, LocaleInfo>,
 }
 
 impl SimpleBackend {
@@ -54,6 +91,7 @@ impl SimpleBackend {
     pub fn new() -> Self {
         SimpleBackend {
             translations: IndexMap::new(),
+            locale_infos: IndexMap::new(),
         }
     }
 
@@ -78,6 +116,17 @@ impl SimpleBackend {
             self.translations.insert(locale.into(), data.clone());
         }
     }
+
+    /// Set the metadata of the given locale.
+    pub fn add_locale_info(&mut self, locale: &str, info: LocaleInfo) {
+        self.locale_infos.insert(locale.into(), info);
+    }
+}
+
+impl Default for SimpleBackend {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Backend for SimpleBackend {
@@ -98,6 +147,10 @@ impl Backend for SimpleBackend {
 
         None
     }
+
+    fn locale_info(&self, locale: &str) -> Option<LocaleInfo> {
+        self.locale_infos.get(locale).cloned()
+    }
 }
 
 impl BackendExt for SimpleBackend {}
@@ -108,6 +161,7 @@ mod tests {
 
     use super::SimpleBackend;
     use super::{Backend, BackendExt};
+    use crate::LocaleInfo;
 
     #[test]
     fn test_simple_backend() {
@@ -158,4 +212,21 @@ mod tests {
 
         assert_eq!(combined.available_locales(), vec!["en", "zh-CN"]);
     }
+
+    #[test]
+    fn test_locale_info() {
+        let mut backend = SimpleBackend::new();
+        backend.add_locale_info("en", LocaleInfo::new("en"));
+        let mut backend2 = SimpleBackend::new();
+        backend2.add_locale_info(
+            "en",
+            LocaleInfo::new("en").with_meta([("direction", "rtl")]),
+        );
+
+        assert_eq!(backend.locale_info("en"), Some(LocaleInfo::new("en")));
+        assert_eq!(backend.locale_info("fr"), None);
+
+        let combined = backend.extend(backend2);
+        assert!(combined.locale_info("en").unwrap().is_rtl());
+    }
 }
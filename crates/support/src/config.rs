@@ -2,12 +2,15 @@
 //! to load and inspect `Cargo.toml` metadata.
 //!
 //! See `Manifest::from_slice`.
+use crate::locale::canonicalize_locale;
+use crate::{LoadOptions, LocaleLayout};
+use indexmap::IndexMap;
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
 use std::fs;
 use std::io;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -22,6 +25,29 @@ pub struct I18nConfig {
     pub generate_version: usize,
     #[serde(default = "generate_extension")]
     pub generate_extension: String,
+    #[serde(default = "generate_file_name")]
+    pub generate_file_name: String,
+    #[serde(default = "namespaced")]
+    pub namespaced: bool,
+    #[serde(default = "layout")]
+    pub layout: LocaleLayout,
+    #[serde(default = "fallback")]
+    pub fallback: Option<String>,
+    /// Canonicalize locales of files and of this config, e.g. `zh_CN` => `zh-CN`
+    #[serde(default = "canonicalize_locales")]
+    pub canonicalize_locales: bool,
+    /// Compile in only `available-locales` by `i18n!`, instead of all locales of the files
+    #[serde(default = "compile_available_locales")]
+    pub compile_available_locales: bool,
+    /// Severities of the rules of `cargo i18n lint` by name, e.g. `untranslated = "off"`
+    #[serde(default = "lint")]
+    pub lint: IndexMap<String, Severity>,
+    /// Macros whose argument is a key for `cargo i18n`, besides `t!`, as `name` or `name:position`
+    #[serde(default = "extract_macros")]
+    pub extract_macros: Vec<String>,
+    /// Functions whose argument is a key for `cargo i18n`, as `name` or `name:position`
+    #[serde(default = "extract_functions")]
+    pub extract_functions: Vec<String>,
 }
 
 fn default_locale() -> String {
@@ -41,6 +67,52 @@ fn generate_version() -> usize {
 fn generate_extension() -> String {
     I18nConfig::default().generate_extension
 }
+fn generate_file_name() -> String {
+    I18nConfig::default().generate_file_name
+}
+fn namespaced() -> bool {
+    I18nConfig::default().namespaced
+}
+fn layout() -> LocaleLayout {
+    I18nConfig::default().layout
+}
+fn fallback() -> Option<String> {
+    I18nConfig::default().fallback
+}
+fn canonicalize_locales() -> bool {
+    I18nConfig::default().canonicalize_locales
+}
+fn compile_available_locales() -> bool {
+    I18nConfig::default().compile_available_locales
+}
+fn lint() -> IndexMap<String, Severity> {
+    I18nConfig::default().lint
+}
+fn extract_macros() -> Vec<String> {
+    I18nConfig::default().extract_macros
+}
+fn extract_functions() -> Vec<String> {
+    I18nConfig::default().extract_functions
+}
+
+/// Severity of a rule of `cargo i18n lint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Off,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Off => "off",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
@@ -56,10 +128,36 @@ impl Default for I18nConfig {
             load_path: "./locales".to_string(),
             generate_version: 2,
             generate_extension: "yaml".to_string(),
+            generate_file_name: "app".to_string(),
+            namespaced: false,
+            layout: LocaleLayout::File,
+            fallback: None,
+            canonicalize_locales: false,
+            compile_available_locales: false,
+            lint: IndexMap::new(),
+            extract_macros: vec![],
+            extract_functions: vec![],
         }
     }
 }
 
+impl I18nConfig {
+    /// Options for loading locales in the same way as `i18n!` does with this config,
+    /// but with the translations to be reviewed, that `cargo i18n` keeps in TODO files
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            namespaced: self.namespaced,
+            layout: self.layout,
+            canonicalize_locales: self.canonicalize_locales,
+            skip_unreviewed: false,
+        }
+    }
+}
+
+/// Load the I18n config of the package in `cargo_root`.
+///
+/// Settings in `[workspace.metadata.i18n]` of the workspace manifest are inherited, unless the package
+/// overrides them. An inherited `load-path` is relative to the workspace root.
 pub fn load(cargo_root: &Path) -> io::Result<I18nConfig> {
     let cargo_file = cargo_root.join("Cargo.toml");
     let mut file = fs::File::open(&cargo_file)
@@ -68,28 +166,130 @@ pub fn load(cargo_root: &Path) -> io::Result<I18nConfig> {
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
 
-    parse(&contents)
+    let mut table = package_table(&parse_manifest(&contents)?);
+    if let Some((workspace_root, workspace_table)) = find_workspace_table(cargo_root)? {
+        inherit(&mut table, workspace_table, &workspace_root);
+    }
+
+    from_table(table)
+}
+
+/// Manifests that [`load`] reads the config from, the one of the package and the one of its workspace
+pub fn manifest_files(cargo_root: &Path) -> io::Result<Vec<PathBuf>> {
+    let cargo_root = dunce::canonicalize(cargo_root)?;
+    let mut files = vec![cargo_root.join("Cargo.toml")];
+    if let Some((workspace_root, _)) = find_workspace_table(&cargo_root)? {
+        files.push(workspace_root.join("Cargo.toml"));
+    }
+    files.dedup();
+    Ok(files)
 }
 
 pub fn parse(contents: &str) -> io::Result<I18nConfig> {
-    if !contents.contains("[i18n]") && !contents.contains("[package.metadata.i18n]") {
-        return Ok(I18nConfig::default());
+    from_table(package_table(&parse_manifest(contents)?))
+}
+
+/// Parse config of a package manifest, with `[workspace.metadata.i18n]` of the workspace manifest
+/// in `workspace_root` as defaults.
+pub fn parse_with_workspace(
+    contents: &str,
+    workspace_contents: &str,
+    workspace_root: &Path,
+) -> io::Result<I18nConfig> {
+    let mut table = package_table(&parse_manifest(contents)?);
+    if let Some(workspace_table) = workspace_table(&parse_manifest(workspace_contents)?) {
+        inherit(&mut table, workspace_table, workspace_root);
+    }
+
+    from_table(table)
+}
+
+fn parse_manifest(contents: &str) -> io::Result<toml::Table> {
+    toml::from_str(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// `[i18n]` or `[package.metadata.i18n]`
+fn package_table(manifest: &toml::Table) -> toml::Table {
+    manifest
+        .get("i18n")
+        .or_else(|| manifest.get("package")?.get("metadata")?.get("i18n"))
+        .and_then(|v| v.as_table())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// `[workspace.metadata.i18n]`
+fn workspace_table(manifest: &toml::Table) -> Option<toml::Table> {
+    manifest
+        .get("workspace")?
+        .get("metadata")?
+        .get("i18n")?
+        .as_table()
+        .cloned()
+}
+
+/// Find the nearest workspace manifest (including the package itself) and return its i18n config.
+fn find_workspace_table(cargo_root: &Path) -> io::Result<Option<(PathBuf, toml::Table)>> {
+    let cargo_root = dunce::canonicalize(cargo_root)?;
+
+    for dir in cargo_root.ancestors() {
+        let Ok(contents) = fs::read_to_string(dir.join("Cargo.toml")) else {
+            continue;
+        };
+        let manifest = parse_manifest(&contents)?;
+        if manifest.contains_key("workspace") {
+            return Ok(workspace_table(&manifest).map(|table| (dir.to_path_buf(), table)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Add settings of the workspace, that are not set by the package.
+fn inherit(table: &mut toml::Table, workspace_table: toml::Table, workspace_root: &Path) {
+    for (key, mut value) in workspace_table {
+        if key == "load-path" {
+            if let Some(path) = value.as_str() {
+                value = toml::Value::String(workspace_root.join(path).display().to_string());
+            }
+        }
+        table.entry(key).or_insert(value);
+    }
+}
+
+fn from_table(table: toml::Table) -> io::Result<I18nConfig> {
+    let mut config: I18nConfig = table
+        .try_into()
+        .map_err(|e: toml::de::Error| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    // Validate locales as BCP-47 tags, canonicalize them if enabled
+    let canonicalize = config.canonicalize_locales;
+    let check = |locale: &mut String| -> io::Result<()> {
+        let canonical = canonicalize_locale(locale)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if canonicalize {
+            *locale = canonical;
+        }
+        Ok(())
+    };
+    config.available_locales.iter_mut().try_for_each(check)?;
+    check(&mut config.default_locale)?;
+    if let Some(fallback) = config.fallback.as_mut() {
+        check(fallback)?;
     }
-    let contents = contents.replace("[package.metadata.i18n]", "[i18n]");
-    let mut config: MainConfig = toml::from_str(&contents)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
 
     // Push default_locale
     config
-        .i18n
         .available_locales
-        .insert(0, config.i18n.default_locale.clone());
+        .insert(0, config.default_locale.clone());
 
-    // unqiue
-    config.i18n.available_locales.sort_unstable();
-    config.i18n.available_locales.dedup();
+    // unqiue, keep the default locale first
+    let mut seen = std::collections::HashSet::new();
+    config
+        .available_locales
+        .retain(|locale| seen.insert(locale.clone()));
 
-    Ok(config.i18n)
+    Ok(config)
 }
 
 #[test]
@@ -105,6 +305,16 @@ fn test_parse() {
     assert_eq!(cfg.default_locale, "en");
     assert_eq!(cfg.available_locales, vec!["en", "zh-CN"]);
     assert_eq!(cfg.load_path, "./my-locales");
+    assert!(!cfg.compile_available_locales);
+
+    let contents = r#"
+        [i18n]
+        load-path = "./my-locales"
+        compile-available-locales = true
+    "#;
+    let cfg = parse(contents).unwrap();
+    assert_eq!(cfg.available_locales, vec!["en"]);
+    assert!(cfg.compile_available_locales);
 
     let contents = r#"
         [i18n]
@@ -130,12 +340,16 @@ fn test_parse_with_metadata() {
         default-locale = "en"
         available-locales = ["zh-CN"]
         load-path = "./my-locales"
+        fallback = "en"
+        generate-file-name = "messages"
     "#;
 
     let cfg = parse(contents).unwrap();
     assert_eq!(cfg.default_locale, "en");
     assert_eq!(cfg.available_locales, vec!["en", "zh-CN"]);
     assert_eq!(cfg.load_path, "./my-locales");
+    assert_eq!(cfg.fallback, Some("en".to_string()));
+    assert_eq!(cfg.generate_file_name, "messages");
 }
 
 #[test]
@@ -151,9 +365,124 @@ fn test_load_default() {
 #[test]
 fn test_load() {
     let workdir = Path::new(env!["CARGO_MANIFEST_DIR"]);
-    let cargo_root = workdir.join("examples/foo");
+    let cargo_root = workdir.join("../../examples/foo");
 
     let cfg = load(&cargo_root).unwrap();
     assert_eq!(cfg.default_locale, "en");
     assert_eq!(cfg.available_locales, vec!["en", "zh-CN"]);
 }
+
+#[test]
+fn test_manifest_files() {
+    let workdir = Path::new(env!["CARGO_MANIFEST_DIR"]);
+    let files =
+        manifest_files(&workdir.join("../../examples/app-workspace/crates/example-base")).unwrap();
+    let root = dunce::canonicalize(workdir.join("../../examples/app-workspace")).unwrap();
+    assert_eq!(
+        files,
+        vec![
+            root.join("crates/example-base/Cargo.toml"),
+            root.join("Cargo.toml")
+        ]
+    );
+
+    let root = dunce::canonicalize(workdir.join("../..")).unwrap();
+    assert_eq!(
+        manifest_files(&root).unwrap(),
+        vec![root.join("Cargo.toml")]
+    );
+}
+
+#[test]
+fn test_parse_with_workspace() {
+    let workspace = r#"
+        [workspace]
+        members = ["app"]
+
+        [workspace.metadata.i18n]
+        available-locales = ["en", "fr"]
+        load-path = "locales"
+        fallback = "en"
+    "#;
+    let contents = r#"
+        [package]
+        name = "app"
+
+        [package.metadata.i18n]
+        available-locales = ["en", "de"]
+    "#;
+
+    let cfg = parse_with_workspace(contents, workspace, Path::new("/work")).unwrap();
+    assert_eq!(cfg.available_locales, vec!["en", "de"]);
+    assert_eq!(
+        Path::new(&cfg.load_path),
+        Path::new("/work").join("locales")
+    );
+    assert_eq!(cfg.fallback, Some("en".to_string()));
+
+    let cfg = parse_with_workspace("", workspace, Path::new("/work")).unwrap();
+    assert_eq!(cfg.available_locales, vec!["en", "fr"]);
+
+    let cfg = parse_with_workspace(contents, "[workspace]", Path::new("/work")).unwrap();
+    assert_eq!(cfg.load_path, "./locales");
+}
+
+#[test]
+fn test_parse_locales() {
+    let contents = r#"
+        [i18n]
+        default-locale = "en_US"
+        available-locales = ["zh_CN", "EN_us", "fr"]
+        fallback = "zh_cn"
+    "#;
+    let cfg = parse(contents).unwrap();
+    assert_eq!(cfg.default_locale, "en_US");
+    assert_eq!(cfg.available_locales, vec!["en_US", "zh_CN", "EN_us", "fr"]);
+
+    let contents = r#"
+        [i18n]
+        default-locale = "en_US"
+        available-locales = ["zh_CN", "EN_us", "fr"]
+        fallback = "zh_cn"
+        canonicalize-locales = true
+    "#;
+    let cfg = parse(contents).unwrap();
+    assert_eq!(cfg.default_locale, "en-US");
+    assert_eq!(cfg.available_locales, vec!["en-US", "zh-CN", "fr"]);
+    assert_eq!(cfg.fallback, Some("zh-CN".to_string()));
+    assert!(cfg.load_options().canonicalize_locales);
+
+    let contents = r#"
+        [i18n]
+        available-locales = ["en", "zh_CN.UTF-8"]
+    "#;
+    let err = parse(contents).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("Invalid locale `zh_CN.UTF-8`"));
+}
+
+#[test]
+fn test_parse_lint() {
+    let contents = r#"
+        [i18n.lint]
+        untranslated = "off"
+        whitespace = "error"
+    "#;
+    let cfg = parse(contents).unwrap();
+    assert_eq!(cfg.lint["untranslated"], Severity::Off);
+    assert_eq!(cfg.lint["whitespace"], Severity::Error);
+    assert!(parse("[i18n.lint]\nquotes = \"fatal\"").is_err());
+}
+
+#[test]
+fn test_parse_extract_names() {
+    let contents = r#"
+        [i18n]
+        extract-macros = ["tr"]
+        extract-functions = ["label:1"]
+    "#;
+    let cfg = parse(contents).unwrap();
+    assert_eq!(cfg.extract_macros, vec!["tr"]);
+    assert_eq!(cfg.extract_functions, vec!["label:1"]);
+    assert!(parse("").unwrap().extract_macros.is_empty());
+}
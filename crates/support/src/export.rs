@@ -0,0 +1,201 @@
+//! Export loaded translations into formats used by other i18n libraries.
+use crate::{file_namespace, file_namespaces, load_locales, LoadOptions};
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Namespace used by i18next when none is given.
+pub const I18NEXT_DEFAULT_NAMESPACE: &str = "translation";
+
+/// CLDR plural categories, mapped to i18next `_zero`, `_one`, ... suffixes.
+const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// Load locales grouped by namespaces derived from file names (see [`file_namespace`]).
+///
+/// Files without a namespace are put into [`I18NEXT_DEFAULT_NAMESPACE`], `REMOVED.*` files are skipped.
+pub fn load_i18next_namespaces(
+    locales_path: &str,
+) -> IndexMap<String, IndexMap<String, IndexMap<String, String>>> {
+    let mut result = IndexMap::new();
+
+    for namespace in file_namespaces(locales_path, &LoadOptions::default()) {
+        let data = load_locales(locales_path, |fname| {
+            let path = Path::new(fname);
+            let removed = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.starts_with("REMOVED."));
+            removed || file_namespace(path) != namespace
+        });
+        if data.is_empty() {
+            continue;
+        }
+
+        let namespace = if namespace.is_empty() {
+            I18NEXT_DEFAULT_NAMESPACE.to_string()
+        } else {
+            namespace
+        };
+        result.insert(namespace, data);
+    }
+
+    result
+}
+
+/// Write translations (the result of `load_locales`) as i18next JSON bundles.
+///
+/// Every locale is written to `<output>/<locale>/<namespace>.json`.
+pub fn write_i18next<P: AsRef<Path>>(
+    output: P,
+    namespace: &str,
+    translations: &IndexMap<String, IndexMap<String, String>>,
+) -> io::Result<()> {
+    for (locale, trs) in translations {
+        let dir = output.as_ref().join(locale);
+        fs::create_dir_all(&dir)?;
+
+        let content = serde_json::to_string_pretty(&to_i18next(trs))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::write(dir.join(format!("{namespace}.json")), content + "\n")?;
+    }
+
+    Ok(())
+}
+
+/// Convert flatten translations of one locale into a nested i18next resource.
+///
+/// - `%{var}` is converted into `{{var}}`
+/// - plural forms (`messages.one`, `messages.other`) are converted into `messages_one`, `messages_other`
+pub fn to_i18next(trs: &IndexMap<String, String>) -> Value {
+    let mut root = Map::new();
+
+    for (key, value) in trs {
+        let key = plural_key(trs, key);
+        insert_nested(&mut root, &key, Value::String(convert_interpolation(value)));
+    }
+
+    Value::Object(root)
+}
+
+/// Turn `foo.one` into `foo_one`, if `foo.other` exists too.
+fn plural_key(trs: &IndexMap<String, String>, key: &str) -> String {
+    if let Some((base, category)) = key.rsplit_once('.') {
+        if PLURAL_CATEGORIES.contains(&category) && trs.contains_key(&format!("{base}.other")) {
+            return format!("{base}_{category}");
+        }
+    }
+
+    key.to_string()
+}
+
+/// Insert value by dotted key into nested objects.
+///
+/// If some part of the path is already a text, the rest of the key is kept as a flat key,
+/// i18next is still able to find it.
+fn insert_nested(map: &mut Map<String, Value>, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((head, rest)) => match map.get(head) {
+            Some(Value::String(_)) => {
+                map.insert(key.to_string(), value);
+            }
+            _ => {
+                let entry = map
+                    .entry(head.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                if let Value::Object(sub) = entry {
+                    insert_nested(sub, rest, value);
+                }
+            }
+        },
+        None => {
+            // Key is also a prefix of other keys, keep them as flat keys
+            if let Some(Value::Object(sub)) = map.insert(key.to_string(), value) {
+                for (sub_key, sub_value) in flatten_object(key, sub) {
+                    map.insert(sub_key, sub_value);
+                }
+            }
+        }
+    }
+}
+
+fn flatten_object(prefix: &str, map: Map<String, Value>) -> Vec<(String, Value)> {
+    let mut result = vec![];
+    for (key, value) in map {
+        let key = format!("{prefix}.{key}");
+        match value {
+            Value::Object(sub) => result.extend(flatten_object(&key, sub)),
+            value => result.push((key, value)),
+        }
+    }
+    result
+}
+
+/// Convert `%{var}` into `{{var}}`
+fn convert_interpolation(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("%{") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        result.push_str("{{");
+        result.push_str(&rest[start + 2..start + end]);
+        result.push_str("}}");
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_interpolation() {
+        assert_eq!(convert_interpolation("Hello"), "Hello");
+        assert_eq!(convert_interpolation("Hello, %{name}!"), "Hello, {{name}}!");
+        assert_eq!(
+            convert_interpolation("%{a} and %{b} %{"),
+            "{{a}} and {{b}} %{"
+        );
+    }
+
+    #[test]
+    fn test_to_i18next() {
+        let trs = IndexMap::from([
+            ("hello".to_string(), "Hello, %{name}!".to_string()),
+            ("messages.zero".to_string(), "No messages".to_string()),
+            ("messages.one".to_string(), "One message".to_string()),
+            (
+                "messages.other".to_string(),
+                "%{count} messages".to_string(),
+            ),
+            ("numbers.one".to_string(), "One".to_string()),
+            ("welcome".to_string(), "Welcome".to_string()),
+            ("welcome.sub".to_string(), "Welcome 1".to_string()),
+        ]);
+
+        let json = to_i18next(&trs);
+        assert_eq!(json["hello"], "Hello, {{name}}!");
+        assert_eq!(json["messages_zero"], "No messages");
+        assert_eq!(json["messages_one"], "One message");
+        assert_eq!(json["messages_other"], "{{count}} messages");
+        assert_eq!(json["numbers"]["one"], "One");
+        assert_eq!(json["welcome"], "Welcome");
+        assert_eq!(json["welcome.sub"], "Welcome 1");
+
+        let trs = IndexMap::from([
+            ("welcome.sub".to_string(), "Welcome 1".to_string()),
+            ("welcome".to_string(), "Welcome".to_string()),
+        ]);
+        let json = to_i18next(&trs);
+        assert_eq!(json["welcome"], "Welcome");
+        assert_eq!(json["welcome.sub"], "Welcome 1");
+    }
+}
@@ -0,0 +1,1008 @@
+//! ICU MessageFormat for translations, e.g.
+//!
+//! ```text
+//! {count, plural, =0 {No files} one {# file} other {# files}}
+//! {gender, select, female {She} male {He} other {They}} invited you.
+//! {price, number, ::.00} / {total, number, percent}
+//! {due, date, medium} {due, time, short} {due, date, ::yyyyMMMd}
+//! ```
+//!
+//! Dates are formatted in UTC with English names, numbers with the separators of the locale.
+use crate::plural::{ordinal_category, plural_category, PluralOperands};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A typed argument of a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Date(SystemTime),
+}
+
+impl fmt::Display for Argument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Argument::Str(s) => write!(f, "{s}"),
+            Argument::Int(n) => write!(f, "{n}"),
+            Argument::Float(n) => write!(f, "{n}"),
+            Argument::Date(time) => write!(
+                f,
+                "{}",
+                format_pattern(&DateTime::from(*time), "yyyy-MM-dd'T'HH:mm:ss'Z'")
+            ),
+        }
+    }
+}
+
+macro_rules! impl_from_for_argument {
+    ($variant:ident, $as:ty, $($ty:ty),+) => {
+        $(
+            impl From<$ty> for Argument {
+                fn from(value: $ty) -> Self {
+                    Argument::$variant(value as $as)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_for_argument!(Int, i64, i8, i16, i32, i64, isize, u8, u16, u32);
+impl_from_for_argument!(Float, f64, f64);
+
+impl From<u64> for Argument {
+    fn from(value: u64) -> Self {
+        i64::try_from(value)
+            .map(Argument::Int)
+            .unwrap_or_else(|_| Argument::Str(value.to_string()))
+    }
+}
+
+impl From<usize> for Argument {
+    fn from(value: usize) -> Self {
+        Argument::from(value as u64)
+    }
+}
+
+impl From<f32> for Argument {
+    fn from(value: f32) -> Self {
+        // Keep the shortest representation of f32, e.g. `0.1` instead of `0.10000000149011612`
+        Argument::Float(value.to_string().parse().unwrap_or(value as f64))
+    }
+}
+
+impl From<SystemTime> for Argument {
+    fn from(value: SystemTime) -> Self {
+        Argument::Date(value)
+    }
+}
+
+impl From<&str> for Argument {
+    fn from(value: &str) -> Self {
+        Argument::Str(value.to_string())
+    }
+}
+
+impl From<String> for Argument {
+    fn from(value: String) -> Self {
+        Argument::Str(value)
+    }
+}
+
+/// Wrapper of a `t!` argument, numbers and `SystemTime` become typed arguments, other values are formatted with `Display`
+#[doc(hidden)]
+pub struct ArgumentWrap<'a, T: ?Sized>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ToTypedArgument {
+    fn to_argument(&self) -> Argument;
+}
+
+impl<T: Copy + Into<Argument>> ToTypedArgument for ArgumentWrap<'_, T> {
+    fn to_argument(&self) -> Argument {
+        (*self.0).into()
+    }
+}
+
+#[doc(hidden)]
+pub trait ToDisplayArgument {
+    fn to_argument(&self) -> Argument;
+}
+
+impl<T: fmt::Display + ?Sized> ToDisplayArgument for &ArgumentWrap<'_, T> {
+    fn to_argument(&self) -> Argument {
+        Argument::Str(self.0.to_string())
+    }
+}
+
+/// Error of parsing a message, with the offset (in chars) where it happens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type Cases = Vec<(String, Vec<Part>)>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    /// `{name}`
+    Arg(String),
+    /// `{name, number, style}`
+    Number(String, Option<String>),
+    /// `{name, date, style}` or `{name, time, style}`
+    Date(String, bool, Option<String>),
+    /// `{name, plural, offset:1 =0 {...} one {...} other {...}}` or `selectordinal`
+    Plural {
+        name: String,
+        ordinal: bool,
+        offset: i64,
+        cases: Cases,
+    },
+    /// `{name, select, a {...} other {...}}`
+    Select(String, Cases),
+    /// `#` in a plural case
+    Hash,
+}
+
+/// A parsed ICU message
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageFormat {
+    parts: Vec<Part>,
+}
+
+impl MessageFormat {
+    pub fn parse(message: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            chars: message.chars().collect(),
+            pos: 0,
+        };
+        let parts = parser.parse_message(false)?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("Unexpected `}`"));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Format the message with the arguments, an argument that is missing is kept as `{name}`
+    pub fn format(&self, locale: &str, args: &[(&str, Argument)]) -> String {
+        let mut out = String::new();
+        format_parts(&mut out, &self.parts, locale, args, None);
+        out
+    }
+}
+
+/// Format a translation as ICU MessageFormat, the parsed message is cached.
+///
+/// If the message is invalid, it is returned as it is.
+pub fn format_cached(locale: &str, message: &'static str, args: &[(&str, Argument)]) -> String {
+    /// Parsed messages, `None` if invalid
+    type Cache = RwLock<HashMap<&'static str, Option<Arc<MessageFormat>>>>;
+    static CACHE: OnceLock<Cache> = OnceLock::new();
+
+    if !message.contains(['{', '\'']) {
+        return message.to_string();
+    }
+
+    let cache = CACHE.get_or_init(Default::default);
+    let cached = cache.read().unwrap().get(message).cloned();
+    let parsed = cached.unwrap_or_else(|| {
+        let parsed = MessageFormat::parse(message).ok().map(Arc::new);
+        cache.write().unwrap().insert(message, parsed.clone());
+        parsed
+    });
+
+    match parsed {
+        Some(parsed) => parsed.format(locale, args),
+        None => message.to_string(),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+            offset: self.pos,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Read until whitespace or one of `stop`
+    fn word(&mut self, stop: &[char]) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !stop.contains(&c))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("Expected `{c}`")));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Parse a message until the end or an unmatched `}`
+    fn parse_message(&mut self, in_plural: bool) -> Result<Vec<Part>, ParseError> {
+        let mut parts = vec![];
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '}' => break,
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.parse_argument()?);
+                }
+                '#' if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Hash);
+                    self.pos += 1;
+                }
+                '%' if self.chars.get(self.pos + 1) == Some(&'{') => {
+                    // Keep `%{var}` of rust-i18n as it is, it is replaced by `t!`
+                    while let Some(c) = self.peek() {
+                        text.push(c);
+                        self.pos += 1;
+                        if c == '}' {
+                            break;
+                        }
+                    }
+                }
+                '\'' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\'') => {
+                            text.push('\'');
+                            self.pos += 1;
+                        }
+                        Some('{' | '}' | '|') => self.quoted(&mut text),
+                        Some('#') if in_plural => self.quoted(&mut text),
+                        _ => text.push('\''),
+                    }
+                }
+                c => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// Quoted text after `'`, until the next single `'`
+    fn quoted(&mut self, text: &mut String) {
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c != '\'' {
+                text.push(c);
+            } else if self.peek() == Some('\'') {
+                text.push('\'');
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn parse_argument(&mut self) -> Result<Part, ParseError> {
+        self.pos += 1;
+        self.skip_whitespace();
+        let name = self.word(&['{', '}', ',']);
+        if name.is_empty() {
+            return Err(self.error("Expected an argument name"));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('}') => {
+                self.pos += 1;
+                return Ok(Part::Arg(name));
+            }
+            Some(',') => self.pos += 1,
+            _ => return Err(self.error("Expected `,` or `}`")),
+        }
+
+        self.skip_whitespace();
+        let kind = self.word(&['{', '}', ',']);
+        self.skip_whitespace();
+        let has_style = self.peek() == Some(',');
+        if has_style {
+            self.pos += 1;
+        }
+
+        let part = match kind.as_str() {
+            "number" | "date" | "time" => {
+                let style = if has_style { Some(self.style()?) } else { None };
+                if kind == "number" {
+                    Part::Number(name, style)
+                } else {
+                    Part::Date(name, kind == "time", style)
+                }
+            }
+            "plural" | "selectordinal" if has_style => {
+                let mut offset = 0;
+                self.skip_whitespace();
+                if self.chars[self.pos..].starts_with(&['o', 'f', 'f', 's', 'e', 't', ':']) {
+                    self.pos += 7;
+                    self.skip_whitespace();
+                    offset = self
+                        .word(&['{', '}'])
+                        .parse()
+                        .map_err(|_| self.error("Invalid offset"))?;
+                }
+                Part::Plural {
+                    name,
+                    ordinal: kind == "selectordinal",
+                    offset,
+                    cases: self.cases(true)?,
+                }
+            }
+            "select" if has_style => Part::Select(name, self.cases(false)?),
+            "plural" | "selectordinal" | "select" => {
+                return Err(self.error(&format!("Expected cases of `{kind}`")))
+            }
+            _ => return Err(self.error(&format!("Unknown argument type `{kind}`"))),
+        };
+
+        self.expect('}')?;
+        Ok(part)
+    }
+
+    /// Style of `number`, `date` and `time`, until `}`
+    fn style(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != '}') {
+            if self.peek() == Some('{') {
+                return Err(self.error("Unexpected `{`"));
+            }
+            self.pos += 1;
+        }
+        Ok(self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string())
+    }
+
+    /// Cases `key {message}` of `plural` and `select`, `other` is required
+    fn cases(&mut self, in_plural: bool) -> Result<Cases, ParseError> {
+        let mut cases = vec![];
+        loop {
+            self.skip_whitespace();
+            if matches!(self.peek(), Some('}') | None) {
+                break;
+            }
+            let key = self.word(&['{', '}']);
+            if key.is_empty() {
+                return Err(self.error("Expected a case"));
+            }
+            self.expect('{')?;
+            let message = self.parse_message(in_plural)?;
+            self.expect('}')?;
+            cases.push((key, message));
+        }
+
+        if !cases.iter().any(|(key, _)| key == "other") {
+            return Err(self.error("Missing `other` case"));
+        }
+        Ok(cases)
+    }
+}
+
+fn format_parts(
+    out: &mut String,
+    parts: &[Part],
+    locale: &str,
+    args: &[(&str, Argument)],
+    hash: Option<&Argument>,
+) {
+    let arg = |name: &str| {
+        args.iter()
+            .find(|(arg_name, _)| *arg_name == name)
+            .map(|(_, value)| value)
+    };
+
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Hash => match hash {
+                Some(value) => out.push_str(&format_number(locale, value, None)),
+                None => out.push('#'),
+            },
+            Part::Arg(name) => match arg(name) {
+                Some(value @ (Argument::Int(_) | Argument::Float(_))) => {
+                    out.push_str(&format_number(locale, value, None))
+                }
+                Some(Argument::Date(time)) => {
+                    out.push_str(&format_date(*time, false, Some("short")))
+                }
+                Some(value) => out.push_str(&value.to_string()),
+                None => out.push_str(&format!("{{{name}}}")),
+            },
+            Part::Number(name, style) => match arg(name) {
+                Some(value) => out.push_str(&format_number(locale, value, style.as_deref())),
+                None => out.push_str(&format!("{{{name}}}")),
+            },
+            Part::Date(name, time, style) => match arg(name) {
+                Some(Argument::Date(value)) => {
+                    out.push_str(&format_date(*value, *time, style.as_deref()))
+                }
+                Some(value) => out.push_str(&value.to_string()),
+                None => out.push_str(&format!("{{{name}}}")),
+            },
+            Part::Plural {
+                name,
+                ordinal,
+                offset,
+                cases,
+            } => {
+                let Some((n, operands)) = arg(name).and_then(|value| number(value, *offset)) else {
+                    out.push_str(&format!("{{{name}}}"));
+                    continue;
+                };
+
+                let exact = arg(name)
+                    .and_then(|value| number(value, 0))
+                    .map(|(value, _)| match value {
+                        Argument::Int(n) => n as f64,
+                        Argument::Float(n) => n,
+                        _ => f64::NAN,
+                    });
+                let category = if *ordinal {
+                    ordinal_category(locale, &operands)
+                } else {
+                    plural_category(locale, &operands)
+                };
+                let case = cases
+                    .iter()
+                    .find(|(key, _)| {
+                        key.strip_prefix('=')
+                            .and_then(|v| v.parse::<f64>().ok())
+                            .is_some_and(|v| Some(v) == exact)
+                    })
+                    .or_else(|| cases.iter().find(|(key, _)| key == category.as_str()))
+                    .or_else(|| cases.iter().find(|(key, _)| key == "other"));
+                if let Some((_, message)) = case {
+                    format_parts(out, message, locale, args, Some(&n));
+                }
+            }
+            Part::Select(name, cases) => {
+                let value = arg(name).map(|value| value.to_string());
+                let case = cases
+                    .iter()
+                    .find(|(key, _)| Some(key) == value.as_ref())
+                    .or_else(|| cases.iter().find(|(key, _)| key == "other"));
+                if let Some((_, message)) = case {
+                    format_parts(out, message, locale, args, hash);
+                }
+            }
+        }
+    }
+}
+
+/// Numeric value of an argument minus the offset, and its plural operands
+fn number(value: &Argument, offset: i64) -> Option<(Argument, PluralOperands)> {
+    let value = match value {
+        Argument::Int(n) => Argument::Int(n.saturating_sub(offset)),
+        Argument::Float(n) => Argument::Float(n - offset as f64),
+        Argument::Str(s) => match s.trim().parse::<i64>() {
+            Ok(n) => Argument::Int(n.saturating_sub(offset)),
+            Err(_) if offset == 0 => {
+                // Keep the visible fraction digits, e.g. `1.50`
+                let operands = PluralOperands::parse(s)?;
+                return Some((Argument::Float(s.trim().parse().ok()?), operands));
+            }
+            Err(_) => Argument::Float(s.trim().parse::<f64>().ok()? - offset as f64),
+        },
+        Argument::Date(_) => return None,
+    };
+
+    let operands = match value {
+        Argument::Int(n) => PluralOperands::from(n),
+        Argument::Float(n) => PluralOperands::from(n),
+        _ => return None,
+    };
+    Some((value, operands))
+}
+
+/// Decimal and grouping separators of the locale
+fn separators(locale: &str) -> (&'static str, &'static str) {
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    match language {
+        "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl" => {
+            (",", ".")
+        }
+        "fr" => (",", "\u{202f}"),
+        "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "sv" | "nb" | "fi" | "bg" | "hu" | "lt"
+        | "lv" | "et" => (",", "\u{a0}"),
+        _ => (".", ","),
+    }
+}
+
+/// Format a number by a style (`integer`, `percent`) or a skeleton (`::percent .00 group-off`)
+fn format_number(locale: &str, value: &Argument, style: Option<&str>) -> String {
+    let Some((value, _)) = number(value, 0) else {
+        return value.to_string();
+    };
+
+    let (mut min_fraction, mut max_fraction) = match value {
+        Argument::Int(_) => (0, 0),
+        _ => (0, 3),
+    };
+    let mut percent = false;
+    let mut grouping = true;
+
+    let tokens: Vec<&str> = match style {
+        Some(style) => match style.strip_prefix("::") {
+            Some(skeleton) => skeleton.split_whitespace().collect(),
+            None => vec![style],
+        },
+        None => vec![],
+    };
+    for token in tokens {
+        match token {
+            "integer" | "precision-integer" => (min_fraction, max_fraction) = (0, 0),
+            "percent" | "%" => percent = true,
+            "group-off" | ",_" => grouping = false,
+            token if token.starts_with('.') && token[1..].chars().all(|c| c == '0' || c == '#') => {
+                min_fraction = token.matches('0').count();
+                max_fraction = token.len() - 1;
+            }
+            _ => {}
+        }
+    }
+
+    let mut n = match value {
+        Argument::Int(n) => n as f64,
+        Argument::Float(n) => n,
+        _ => unreachable!(),
+    };
+    if percent {
+        n *= 100.0;
+        if style.is_some_and(|s| !s.starts_with("::")) {
+            max_fraction = 0;
+        }
+    }
+
+    let text = match value {
+        Argument::Int(i) if !percent && max_fraction == 0 => i.unsigned_abs().to_string(),
+        _ => format!("{:.*}", max_fraction, n.abs()),
+    };
+    let (int, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    let mut fraction = fraction.to_string();
+    while fraction.len() > min_fraction && fraction.ends_with('0') {
+        fraction.pop();
+    }
+    while fraction.len() < min_fraction {
+        fraction.push('0');
+    }
+
+    let (decimal_separator, group_separator) = separators(locale);
+    let mut out = String::new();
+    if n < 0.0 && (int.chars().chain(fraction.chars()).any(|c| c != '0')) {
+        out.push('-');
+    }
+    for (i, c) in int.chars().enumerate() {
+        if grouping && i > 0 && (int.len() - i) % 3 == 0 {
+            out.push_str(group_separator);
+        }
+        out.push(c);
+    }
+    if !fraction.is_empty() {
+        out.push_str(decimal_separator);
+        out.push_str(&fraction);
+    }
+    if percent {
+        out.push('%');
+    }
+    out
+}
+
+/// Date and time in UTC
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    weekday: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl From<SystemTime> for DateTime {
+    fn from(time: SystemTime) -> Self {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+        };
+        let days = secs.div_euclid(86400);
+        let secs = secs.rem_euclid(86400) as u32;
+
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            weekday: (days + 4).rem_euclid(7) as u32,
+            hour: secs / 3600,
+            minute: secs / 60 % 60,
+            second: secs % 60,
+        }
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Format a date by a style (`short`, `medium`, `long`, `full`) or a skeleton (`::yyyyMMMd`)
+fn format_date(time: SystemTime, is_time: bool, style: Option<&str>) -> String {
+    let pattern = match (is_time, style.unwrap_or("medium")) {
+        (_, style) if style.starts_with("::") => skeleton_pattern(&style[2..]),
+        (false, "short") => "M/d/yy".to_string(),
+        (false, "long") => "MMMM d, y".to_string(),
+        (false, "full") => "EEEE, MMMM d, y".to_string(),
+        (false, _) => "MMM d, y".to_string(),
+        (true, "short") => "h:mm a".to_string(),
+        (true, "long" | "full") => "h:mm:ss a z".to_string(),
+        (true, _) => "h:mm:ss a".to_string(),
+    };
+    format_pattern(&DateTime::from(time), &pattern)
+}
+
+/// Pattern of a skeleton, fields are written in the order of `en`
+fn skeleton_pattern(skeleton: &str) -> String {
+    let field = |c: char| skeleton.matches(c).count();
+    let (year, month, day, weekday) = (field('y'), field('M'), field('d'), field('E'));
+    let hour12 = field('h') + field('j');
+    let (hour24, minute, second) = (field('H'), field('m'), field('s'));
+
+    let mut date = vec![];
+    if month >= 3 {
+        let mut text = "M".repeat(month);
+        if day > 0 {
+            text += &format!(" {}", "d".repeat(day));
+        }
+        if year > 0 {
+            text += &format!(", {}", "y".repeat(year));
+        }
+        date.push(text);
+    } else {
+        let numeric: Vec<String> = [("M", month), ("d", day), ("y", year)]
+            .iter()
+            .filter(|(_, n)| *n > 0)
+            .map(|(c, n)| c.repeat(*n))
+            .collect();
+        if !numeric.is_empty() {
+            date.push(numeric.join("/"));
+        }
+    }
+    if weekday > 0 {
+        date.insert(0, "E".repeat(weekday));
+    }
+
+    let mut time = vec![];
+    if hour24 > 0 {
+        time.push("H".repeat(hour24.max(2)));
+    } else if hour12 > 0 {
+        time.push("h".to_string());
+    }
+    if minute > 0 {
+        time.push("mm".to_string());
+    }
+    if second > 0 {
+        time.push("ss".to_string());
+    }
+    let mut time = time.join(":");
+    if hour12 > 0 && hour24 == 0 {
+        time += " a";
+    }
+
+    date.into_iter()
+        .chain(Some(time).filter(|t| !t.is_empty()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Format with a date pattern, e.g. `MMM d, y`, text in `'` is literal
+fn format_pattern(dt: &DateTime, pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let mut n = 1;
+        while i + n < chars.len() && chars[i + n] == c {
+            n += 1;
+        }
+
+        match c {
+            '\'' => {
+                if n >= 2 {
+                    out.push('\'');
+                    i += 2;
+                    continue;
+                }
+                i += 1;
+                while i < chars.len() && chars[i] != '\'' {
+                    out.push(chars[i]);
+                    i += 1;
+                }
+                i += 1;
+                continue;
+            }
+            'y' if n == 2 => out.push_str(&format!("{:02}", dt.year.rem_euclid(100))),
+            'y' => out.push_str(&format!("{:0n$}", dt.year)),
+            'M' | 'L' => match n {
+                1 | 2 => out.push_str(&format!("{:0n$}", dt.month)),
+                3 => out.push_str(&MONTHS[dt.month as usize - 1][..3]),
+                _ => out.push_str(MONTHS[dt.month as usize - 1]),
+            },
+            'd' => out.push_str(&format!("{:0n$}", dt.day)),
+            'E' if n >= 4 => out.push_str(WEEKDAYS[dt.weekday as usize]),
+            'E' => out.push_str(&WEEKDAYS[dt.weekday as usize][..3]),
+            'H' => out.push_str(&format!("{:0n$}", dt.hour)),
+            'h' => out.push_str(&format!("{:0n$}", (dt.hour + 11) % 12 + 1)),
+            'm' => out.push_str(&format!("{:0n$}", dt.minute)),
+            's' => out.push_str(&format!("{:0n$}", dt.second)),
+            'a' => out.push_str(if dt.hour < 12 { "AM" } else { "PM" }),
+            'z' => out.push_str("UTC"),
+            c => {
+                for _ in 0..n {
+                    out.push(c);
+                }
+            }
+        }
+        i += n;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn format(message: &str, locale: &str, args: &[(&str, Argument)]) -> String {
+        MessageFormat::parse(message).unwrap().format(locale, args)
+    }
+
+    #[test]
+    fn test_plural() {
+        let message = "{count, plural, =0 {No files} one {# file} other {# files}}";
+        assert_eq!(format(message, "en", &[("count", 0.into())]), "No files");
+        assert_eq!(format(message, "en", &[("count", 1.into())]), "1 file");
+        assert_eq!(
+            format(message, "en", &[("count", 1200.into())]),
+            "1,200 files"
+        );
+        assert_eq!(
+            format(message, "en", &[("count", "1.5".into())]),
+            "1.5 files"
+        );
+        assert_eq!(
+            format(message, "de", &[("count", 1200.into())]),
+            "1.200 files"
+        );
+        assert_eq!(format(message, "en", &[]), "{count}");
+
+        let message = "{count, plural, one {# файл} few {# файла} many {# файлов} other {# файла}}";
+        assert_eq!(format(message, "ru", &[("count", 3.into())]), "3 файла");
+        assert_eq!(format(message, "ru", &[("count", 11.into())]), "11 файлов");
+
+        let message = "{n, plural, offset:1 =0 {Nobody} =1 {{name}} one {{name} and # other} other {{name} and # others}}";
+        let args = |n: i32| [("n", n.into()), ("name", "Jason".into())];
+        assert_eq!(format(message, "en", &args(0)), "Nobody");
+        assert_eq!(format(message, "en", &args(1)), "Jason");
+        assert_eq!(format(message, "en", &args(2)), "Jason and 1 other");
+        assert_eq!(format(message, "en", &args(3)), "Jason and 2 others");
+        // No overflow by the offset
+        let args = [("n", Argument::Int(i64::MIN)), ("name", "Jason".into())];
+        assert!(format(message, "en", &args).starts_with("Jason and -9,223,372,036,854,775,808"));
+
+        let message = "{n, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}";
+        assert_eq!(format(message, "en", &[("n", 22.into())]), "22nd");
+        assert_eq!(format(message, "en", &[("n", 13.into())]), "13th");
+    }
+
+    #[test]
+    fn test_select() {
+        let message = "{gender, select, female {She} male {He} other {They}} invited {guests, plural, one {one guest} other {# guests}}.";
+        let args = |gender: &str| [("gender", gender.into()), ("guests", 2.into())];
+        assert_eq!(
+            format(message, "en", &args("female")),
+            "She invited 2 guests."
+        );
+        assert_eq!(
+            format(message, "en", &args("unknown")),
+            "They invited 2 guests."
+        );
+    }
+
+    #[test]
+    fn test_number() {
+        let args = [("n", 1234.5678.into()), ("r", 0.256.into())];
+        assert_eq!(format("{n, number}", "en", &args), "1,234.568");
+        assert_eq!(format("{n, number}", "fr", &args), "1\u{202f}234,568");
+        assert_eq!(format("{n, number, integer}", "en", &args), "1,235");
+        assert_eq!(
+            format("{n, number, ::.00 group-off}", "en", &args),
+            "1234.57"
+        );
+        assert_eq!(format("{r, number, percent}", "en", &args), "26%");
+        assert_eq!(format("{r, number, ::percent .0}", "en", &args), "25.6%");
+        assert_eq!(format("{n}", "en", &[("n", (-1000).into())]), "-1,000");
+        assert_eq!(format("{n, number}", "en", &[("n", "abc".into())]), "abc");
+    }
+
+    #[test]
+    fn test_date() {
+        // 2023-11-05T14:03:09Z, Sunday
+        let time = UNIX_EPOCH + Duration::from_secs(1699192989);
+        let args = [("d", time.into())];
+        assert_eq!(format("{d, date}", "en", &args), "Nov 5, 2023");
+        assert_eq!(format("{d, date, short}", "en", &args), "11/5/23");
+        assert_eq!(
+            format("{d, date, full}", "en", &args),
+            "Sunday, November 5, 2023"
+        );
+        assert_eq!(format("{d, time, short}", "en", &args), "2:03 PM");
+        assert_eq!(format("{d, time}", "en", &args), "2:03:09 PM");
+        assert_eq!(format("{d, date, ::yyyyMMMd}", "en", &args), "Nov 5, 2023");
+        assert_eq!(format("{d, date, ::yyyyMMdd}", "en", &args), "11/05/2023");
+        assert_eq!(format("{d, date, ::EEEjmm}", "en", &args), "Sun, 2:03 PM");
+        assert_eq!(format("{d, time, ::Hms}", "en", &args), "14:03:09");
+        assert_eq!(Argument::from(time).to_string(), "2023-11-05T14:03:09Z");
+
+        let time = UNIX_EPOCH - Duration::from_secs(86400);
+        assert_eq!(
+            format("{d, date}", "en", &[("d", time.into())]),
+            "Dec 31, 1969"
+        );
+    }
+
+    #[test]
+    fn test_quotes() {
+        assert_eq!(
+            format("It''s '{name}' and '#'", "en", &[]),
+            "It's {name} and '#'"
+        );
+        assert_eq!(
+            format("{n, plural, other {'#' is #}}", "en", &[("n", 5.into())]),
+            "# is 5"
+        );
+        assert_eq!(format("I'm here", "en", &[]), "I'm here");
+        assert_eq!(
+            format("{n, plural, other {# in %{dir}}}", "en", &[("n", 5.into())]),
+            "5 in %{dir}"
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        for message in [
+            "{",
+            "{}",
+            "{name",
+            "Hello}",
+            "{n, plural, one {#}}",
+            "{n, foo}",
+            "{n, select}",
+            "{n, plural, other {#}",
+        ] {
+            assert!(MessageFormat::parse(message).is_err(), "{message}");
+        }
+    }
+
+    #[test]
+    // The same as `t!` does, the borrow selects between typed and `Display` arguments
+    #[allow(clippy::needless_borrow)]
+    fn test_typed_argument() {
+        let arg = |value: Argument| value;
+        assert_eq!(arg((&ArgumentWrap(&5)).to_argument()), Argument::Int(5));
+        assert_eq!(
+            arg((&ArgumentWrap(&2.5f32)).to_argument()),
+            Argument::Float(2.5)
+        );
+        assert_eq!(
+            arg((&ArgumentWrap(&"Jason")).to_argument()),
+            Argument::Str("Jason".into())
+        );
+        assert_eq!(
+            arg((&ArgumentWrap(&"Jason".to_string())).to_argument()),
+            Argument::Str("Jason".into())
+        );
+        assert_eq!(
+            arg((&ArgumentWrap(&'c')).to_argument()),
+            Argument::Str("c".into())
+        );
+        assert_eq!(Argument::from(0.1f32).to_string(), "0.1");
+        assert_eq!(Argument::from(u64::MAX).to_string(), u64::MAX.to_string());
+    }
+
+    #[test]
+    fn test_format_cached() {
+        let message = "{count, plural, one {# file} other {# files}}";
+        assert_eq!(
+            format_cached("en", message, &[("count", 2.into())]),
+            "2 files"
+        );
+        assert_eq!(
+            format_cached("en", message, &[("count", 1.into())]),
+            "1 file"
+        );
+        assert_eq!(format_cached("en", "{invalid", &[]), "{invalid");
+    }
+}
@@ -0,0 +1,473 @@
+use crate::{Backend, BackendExt};
+use indexmap::map::IndexMap;
+use indexmap::IndexSet;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{OnceLock, RwLock};
+
+/// Text of a message, borrowed from a static blob or at a byte range of a decompressed table
+/// (by the index of its blob)
+#[derive(Clone)]
+enum Text {
+    Static(&'static str),
+    #[cfg(feature = "compress")]
+    Decompressed(usize, Range<usize>),
+}
+
+type Messages = HashMap<&'static str, Text>;
+
+/// Compact encoding of translations of all locales, created by [`encode_compact`].
+pub struct CompactTranslations {
+    /// Keys of all locales, stored only once.
+    pub keys: Vec<u8>,
+    /// `(locale, table)` pairs, a table contains unique values and `(key index, value index)` pairs.
+    pub locales: Vec<(String, Vec<u8>)>,
+}
+
+impl CompactTranslations {
+    /// Total size of the encoded data in bytes.
+    pub fn size(&self) -> usize {
+        self.keys.len() + self.locales.iter().map(|(_, t)| t.len()).sum::<usize>()
+    }
+}
+
+/// Encode translations of one locale into a compact blob for [`LazyBackend`].
+///
+/// Every entry is written as `<key len><key><value len><value>`, lengths are LEB128 varints.
+pub fn encode_translations(trs: &IndexMap<String, String>) -> Vec<u8> {
+    let mut blob = Vec::new();
+    for (key, value) in trs {
+        for text in [key, value] {
+            write_varint(&mut blob, text.len());
+            blob.extend_from_slice(text.as_bytes());
+        }
+    }
+    blob
+}
+
+/// Encode translations of all locales for [`LazyBackend::compact`].
+///
+/// Keys are written once into a shared table, and every locale only keeps its unique values
+/// and the `(key index, value index)` pairs. With `compress`, locale tables are compressed with LZ4
+/// (for [`LazyBackend::compressed`]).
+pub fn encode_compact(
+    translations: &IndexMap<String, IndexMap<String, String>>,
+    compress: bool,
+) -> CompactTranslations {
+    let keys = translations
+        .values()
+        .flat_map(|trs| trs.keys())
+        .collect::<IndexSet<_>>();
+
+    let mut keys_blob = Vec::new();
+    write_varint(&mut keys_blob, keys.len());
+    for key in &keys {
+        write_varint(&mut keys_blob, key.len());
+        keys_blob.extend_from_slice(key.as_bytes());
+    }
+
+    let locales = translations
+        .iter()
+        .map(|(locale, trs)| {
+            let values = trs.values().collect::<IndexSet<_>>();
+
+            let mut table = Vec::new();
+            write_varint(&mut table, values.len());
+            for value in &values {
+                write_varint(&mut table, value.len());
+                table.extend_from_slice(value.as_bytes());
+            }
+            write_varint(&mut table, trs.len());
+            for (key, value) in trs {
+                write_varint(&mut table, keys.get_index_of(key).unwrap());
+                write_varint(&mut table, values.get_index_of(value).unwrap());
+            }
+
+            if compress {
+                table = compress_table(&table);
+            }
+            (locale.clone(), table)
+        })
+        .collect();
+
+    CompactTranslations {
+        keys: keys_blob,
+        locales,
+    }
+}
+
+#[cfg(feature = "compress")]
+fn compress_table(table: &[u8]) -> Vec<u8> {
+    lz4_flex::compress_prepend_size(table)
+}
+
+#[cfg(not(feature = "compress"))]
+fn compress_table(_table: &[u8]) -> Vec<u8> {
+    panic!("Compressed translations require the `compress` feature of rust-i18n");
+}
+
+fn write_varint(blob: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        blob.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    blob.push(n as u8);
+}
+
+fn read_varint(blob: &[u8], pos: &mut usize) -> usize {
+    let mut n = 0;
+    let mut shift = 0;
+    loop {
+        let byte = blob[*pos];
+        *pos += 1;
+        n |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return n;
+        }
+        shift += 7;
+    }
+}
+
+/// Byte range of the text at `pos` of the blob
+fn read_range(blob: &[u8], pos: &mut usize) -> Range<usize> {
+    let len = read_varint(blob, pos);
+    let range = *pos..*pos + len;
+    std::str::from_utf8(&blob[range.clone()]).expect("Invalid translations blob");
+    *pos += len;
+    range
+}
+
+fn read_str(blob: &'static [u8], pos: &mut usize) -> &'static str {
+    let range = read_range(blob, pos);
+    str_at(blob, range)
+}
+
+/// Text at the byte range of the blob, checked by [`read_range`]
+fn str_at(blob: &[u8], range: Range<usize>) -> &str {
+    std::str::from_utf8(&blob[range]).expect("Invalid translations blob")
+}
+
+/// Decode blob created by [`encode_translations`], texts are borrowed from the blob.
+fn decode_translations(blob: &'static [u8]) -> Messages {
+    let mut messages = Messages::new();
+    let mut pos = 0;
+    while pos < blob.len() {
+        let key = read_str(blob, &mut pos);
+        let value = read_str(blob, &mut pos);
+        messages.insert(key, Text::Static(value));
+    }
+    messages
+}
+
+fn decode_keys(blob: &'static [u8]) -> Vec<&'static str> {
+    let mut pos = 0;
+    let len = read_varint(blob, &mut pos);
+    (0..len).map(|_| read_str(blob, &mut pos)).collect()
+}
+
+/// Decode a locale table created by [`encode_compact`], with keys from the shared key table,
+/// values are the byte ranges of their texts in the table.
+fn decode_compact(keys: &[&'static str], table: &[u8]) -> HashMap<&'static str, Range<usize>> {
+    let mut pos = 0;
+    let len = read_varint(table, &mut pos);
+    let values = (0..len)
+        .map(|_| read_range(table, &mut pos))
+        .collect::<Vec<_>>();
+
+    let len = read_varint(table, &mut pos);
+    let mut messages = HashMap::with_capacity(len);
+    for _ in 0..len {
+        let key = keys[read_varint(table, &mut pos)];
+        let value = values[read_varint(table, &mut pos)].clone();
+        messages.insert(key, value);
+    }
+    messages
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Plain,
+    Compact,
+    #[cfg(feature = "compress")]
+    Compressed,
+}
+
+/// Backend with translations embedded as static blobs (see [`encode_translations`] and [`encode_compact`]).
+///
+/// A locale is decoded on its first use only, and can be unloaded again by [`Backend::unload_locale`].
+pub struct LazyBackend {
+    format: Format,
+    keys: &'static [u8],
+    blobs: &'static [(&'static str, &'static [u8])],
+    decoded_keys: OnceLock<Vec<&'static str>>,
+    /// Decompressed tables, by the index of their blob
+    #[cfg(feature = "compress")]
+    decompressed: Box<[OnceLock<Box<[u8]>>]>,
+    loaded: RwLock<HashMap<&'static str, Messages>>,
+}
+
+impl LazyBackend {
+    /// Create a new LazyBackend from `(locale, blob)` pairs.
+    pub fn new(blobs: &'static [(&'static str, &'static [u8])]) -> Self {
+        Self::with_format(Format::Plain, &[], blobs)
+    }
+
+    /// Create a new LazyBackend from the key table and `(locale, table)` pairs of [`encode_compact`].
+    pub fn compact(keys: &'static [u8], blobs: &'static [(&'static str, &'static [u8])]) -> Self {
+        Self::with_format(Format::Compact, keys, blobs)
+    }
+
+    /// Same as [`LazyBackend::compact`], but locale tables are compressed.
+    ///
+    /// A table is decompressed once on the first use of the locale and is kept until the backend is dropped,
+    /// as texts returned by [`Backend::translate`] borrow from it, [`Backend::unload_locale`] frees only
+    /// the lookup index.
+    #[cfg(feature = "compress")]
+    pub fn compressed(
+        keys: &'static [u8],
+        blobs: &'static [(&'static str, &'static [u8])],
+    ) -> Self {
+        Self::with_format(Format::Compressed, keys, blobs)
+    }
+
+    fn with_format(
+        format: Format,
+        keys: &'static [u8],
+        blobs: &'static [(&'static str, &'static [u8])],
+    ) -> Self {
+        LazyBackend {
+            format,
+            keys,
+            blobs,
+            decoded_keys: OnceLock::new(),
+            #[cfg(feature = "compress")]
+            decompressed: blobs.iter().map(|_| OnceLock::new()).collect(),
+            loaded: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Return the locales that are currently decoded
+    pub fn loaded_locales(&self) -> Vec<&'static str> {
+        let mut locales = self
+            .loaded
+            .read()
+            .unwrap()
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        locales.sort();
+        locales
+    }
+
+    fn load(&self, locale: &str) -> bool {
+        let Some(index) = self.blobs.iter().position(|(l, _)| *l == locale) else {
+            return false;
+        };
+        let (locale, blob) = self.blobs[index];
+
+        let mut loaded = self.loaded.write().unwrap();
+        loaded.entry(locale).or_insert_with(|| match self.format {
+            Format::Plain => decode_translations(blob),
+            Format::Compact => decode_compact(self.keys(), blob)
+                .into_iter()
+                .map(|(key, range)| (key, Text::Static(str_at(blob, range))))
+                .collect(),
+            #[cfg(feature = "compress")]
+            Format::Compressed => decode_compact(self.keys(), self.decompress(index))
+                .into_iter()
+                .map(|(key, range)| (key, Text::Decompressed(index, range)))
+                .collect(),
+        });
+        true
+    }
+
+    fn keys(&self) -> &[&'static str] {
+        self.decoded_keys.get_or_init(|| decode_keys(self.keys))
+    }
+
+    fn text(&self, text: Text) -> &str {
+        match text {
+            Text::Static(text) => text,
+            #[cfg(feature = "compress")]
+            Text::Decompressed(index, range) => str_at(self.decompress(index), range),
+        }
+    }
+
+    #[cfg(feature = "compress")]
+    fn decompress(&self, index: usize) -> &[u8] {
+        self.decompressed[index].get_or_init(|| {
+            lz4_flex::decompress_size_prepended(self.blobs[index].1)
+                .expect("Invalid translations blob")
+                .into_boxed_slice()
+        })
+    }
+}
+
+impl Backend for LazyBackend {
+    fn available_locales(&self) -> Vec<&str> {
+        let mut locales = self.blobs.iter().map(|(l, _)| *l).collect::<Vec<_>>();
+        locales.sort();
+        locales
+    }
+
+    fn translate(&self, locale: &str, key: &str) -> Option<&str> {
+        if let Some(messages) = self.loaded.read().unwrap().get(locale) {
+            let text = messages.get(key)?.clone();
+            return Some(self.text(text));
+        }
+
+        if !self.load(locale) {
+            return None;
+        }
+        self.translate(locale, key)
+    }
+
+    fn unload_locale(&self, locale: &str) {
+        self.loaded.write().unwrap().remove(locale);
+    }
+}
+
+impl BackendExt for LazyBackend {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let trs = IndexMap::from([
+            ("hello".to_string(), "Hello".to_string()),
+            ("long".to_string(), "a".repeat(300)),
+            ("empty".to_string(), "".to_string()),
+            ("你好".to_string(), "世界".to_string()),
+        ]);
+
+        let blob = Box::leak(encode_translations(&trs).into_boxed_slice());
+        let messages = decode_translations(blob);
+        assert_eq!(messages.len(), 4);
+        for (key, value) in &trs {
+            assert!(
+                matches!(messages.get(key.as_str()), Some(Text::Static(text)) if text == value)
+            );
+        }
+    }
+
+    #[test]
+    fn test_lazy_backend() {
+        let en = IndexMap::from([("hello".to_string(), "Hello".to_string())]);
+        let zh = IndexMap::from([("hello".to_string(), "你好".to_string())]);
+        let blobs = Box::leak(Box::new([
+            (
+                "zh-CN",
+                &*Box::leak(encode_translations(&zh).into_boxed_slice()),
+            ),
+            (
+                "en",
+                &*Box::leak(encode_translations(&en).into_boxed_slice()),
+            ),
+        ]));
+
+        let backend = LazyBackend::new(blobs);
+        assert_eq!(backend.available_locales(), vec!["en", "zh-CN"]);
+        assert!(backend.loaded_locales().is_empty());
+
+        assert_eq!(backend.translate("en", "hello"), Some("Hello"));
+        assert_eq!(backend.translate("en", "foo"), None);
+        assert_eq!(backend.translate("fr", "hello"), None);
+        assert_eq!(backend.loaded_locales(), vec!["en"]);
+
+        assert_eq!(backend.translate("zh-CN", "hello"), Some("你好"));
+        assert_eq!(backend.loaded_locales(), vec!["en", "zh-CN"]);
+
+        backend.unload_locale("en");
+        assert_eq!(backend.loaded_locales(), vec!["zh-CN"]);
+        assert_eq!(backend.translate("en", "hello"), Some("Hello"));
+    }
+
+    fn leak_compact(
+        data: CompactTranslations,
+    ) -> (&'static [u8], &'static [(&'static str, &'static [u8])]) {
+        let blobs = data
+            .locales
+            .into_iter()
+            .map(|(locale, table)| {
+                (
+                    &*Box::leak(locale.into_boxed_str()),
+                    &*Box::leak(table.into_boxed_slice()),
+                )
+            })
+            .collect::<Vec<_>>();
+        (
+            Box::leak(data.keys.into_boxed_slice()),
+            Box::leak(blobs.into_boxed_slice()),
+        )
+    }
+
+    fn compact_translations() -> IndexMap<String, IndexMap<String, String>> {
+        IndexMap::from([
+            (
+                "en".to_string(),
+                IndexMap::from([
+                    ("ok".to_string(), "OK".to_string()),
+                    ("button.ok".to_string(), "OK".to_string()),
+                    ("hello".to_string(), "Hello".to_string()),
+                    ("welcome".to_string(), "Welcome".to_string()),
+                ]),
+            ),
+            (
+                "zh-CN".to_string(),
+                IndexMap::from([
+                    ("hello".to_string(), "你好".to_string()),
+                    ("ok".to_string(), "好".to_string()),
+                    ("button.ok".to_string(), "好".to_string()),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_compact_backend() {
+        let translations = compact_translations();
+        let data = encode_compact(&translations, false);
+        let plain_size: usize = translations
+            .values()
+            .map(|trs| encode_translations(trs).len())
+            .sum();
+        assert!(data.size() < plain_size);
+
+        let (keys, blobs) = leak_compact(data);
+        assert_eq!(
+            decode_keys(keys),
+            vec!["ok", "button.ok", "hello", "welcome"]
+        );
+
+        let backend = LazyBackend::compact(keys, blobs);
+        assert_eq!(backend.available_locales(), vec!["en", "zh-CN"]);
+        assert_eq!(backend.translate("en", "ok"), Some("OK"));
+        assert_eq!(backend.translate("en", "button.ok"), Some("OK"));
+        assert_eq!(backend.translate("zh-CN", "hello"), Some("你好"));
+        assert_eq!(backend.translate("zh-CN", "button.ok"), Some("好"));
+        assert_eq!(backend.translate("zh-CN", "welcome"), None);
+        assert_eq!(backend.loaded_locales(), vec!["en", "zh-CN"]);
+
+        backend.unload_locale("en");
+        assert_eq!(backend.translate("en", "hello"), Some("Hello"));
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_compressed_backend() {
+        let (keys, blobs) = leak_compact(encode_compact(&compact_translations(), true));
+
+        let backend = LazyBackend::compressed(keys, blobs);
+        assert_eq!(backend.translate("en", "button.ok"), Some("OK"));
+        assert_eq!(backend.translate("zh-CN", "ok"), Some("好"));
+
+        // The table is decompressed only once, not again after unloading
+        let hello = backend.translate("zh-CN", "hello").unwrap().as_ptr();
+        backend.unload_locale("zh-CN");
+        assert_eq!(backend.loaded_locales(), vec!["en"]);
+        assert_eq!(backend.translate("zh-CN", "hello"), Some("你好"));
+        assert_eq!(backend.translate("zh-CN", "hello").unwrap().as_ptr(), hello);
+    }
+}
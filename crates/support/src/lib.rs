@@ -1,20 +1,96 @@
 use indexmap::map::IndexMap;
+use indexmap::IndexSet;
+use locale::canonicalize_locale;
+use serde_derive::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 mod backend;
 pub use backend::{Backend, BackendExt, SimpleBackend};
+mod lazy_backend;
+pub use lazy_backend::{encode_compact, encode_translations, CompactTranslations, LazyBackend};
+mod locale_info;
+pub use locale_info::{Direction, LocaleInfo, META_KEY};
 pub mod config;
+pub mod export;
+pub mod icu;
+pub mod locale;
+pub mod plural;
 
 type Locale = String;
 type Value = serde_json::Value;
 type Translations = IndexMap<Locale, Value>;
 
+/// Separator between key and context of a message, e.g. `Open|menu` for `t!("Open", context = "menu")`
+pub const CONTEXT_SEPARATOR: &str = "|";
+
+/// Key of a message with context, under which the translation is stored
+pub fn context_key(key: &str, context: &str) -> String {
+    format!("{key}{CONTEXT_SEPARATOR}{context}")
+}
+
+/// The variant of a select map, that is used when no other variant matches
+pub const SELECT_OTHER: &str = "other";
+
+/// Key of a variant of a select map, e.g. `invite.%{gender}.female` for
+///
+/// ```yml
+/// invite:
+///   "%{gender}":
+///     female: She invited you
+///     other: They invited you
+/// ```
+pub fn variant_key(key: &str, arg: &str, variant: &str) -> String {
+    format!("{key}.%{{{arg}}}.{variant}")
+}
+
+/// Name of the argument of a select map (a map with the single key `%{arg}`), e.g. `gender`
+fn select_arg(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(o) if o.len() == 1 => {
+            let (key, variants) = o.iter().next()?;
+            if !variants.is_object() {
+                return None;
+            }
+            key.strip_prefix("%{")?.strip_suffix('}')
+        }
+        _ => None,
+    }
+}
+
 pub fn is_debug() -> bool {
     std::env::var("RUST_I18N_DEBUG").unwrap_or_else(|_| "0".to_string()) == "1"
 }
 
+/// Marker of TODO entries prefilled with the translation of a similar text, to be reviewed
+pub const LABEL_FUZZY: &str = "FUZZY";
+/// Marker of TODO entries, whose translation was made for an older text of the default locale
+pub const LABEL_STALE: &str = "STALE";
+
+/// Is the translation marked to be reviewed (see [`LABEL_FUZZY`], [`LABEL_STALE`]),
+/// such translations of `TODO.*` files are not used at runtime.
+pub fn is_unreviewed(value: &str) -> bool {
+    [LABEL_FUZZY, LABEL_STALE]
+        .iter()
+        .any(|label| value.starts_with(&format!("{label} ")))
+}
+
+/// Is the file a `TODO.*` file of `cargo i18n`, that has the translations to be reviewed
+fn is_todo_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|name| name.starts_with("TODO."))
+}
+
+/// Remove translations to be reviewed (see [`is_unreviewed`]) from nested translations
+fn remove_unreviewed(value: &mut Value) {
+    if let Value::Object(map) = value {
+        map.retain(|_, value| !value.as_str().is_some_and(is_unreviewed));
+        map.values_mut().for_each(remove_unreviewed);
+    }
+}
+
 /// Merge JSON Values, merge b into a
 fn merge_value(a: &mut Value, b: &Value) {
     match (a, b) {
@@ -29,12 +105,81 @@ fn merge_value(a: &mut Value, b: &Value) {
     }
 }
 
+/// Where the locale of a locale file is taken from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocaleLayout {
+    /// Last segment of the file name, e.g. `locales/view.en.yml`
+    #[default]
+    File,
+    /// First directory below the locales path, e.g. `locales/en/view.yml`.
+    ///
+    /// Files directly in the locales path still use the file name.
+    Directory,
+}
+
+impl std::str::FromStr for LocaleLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(LocaleLayout::File),
+            "directory" => Ok(LocaleLayout::Directory),
+            _ => Err(format!(
+                "Invalid locale layout `{}`, expected `file` or `directory`",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for [`load_locales_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Prefix keys with the namespace of their file (see [`file_namespace`]),
+    /// e.g. `title` in `view.en.yml` is loaded as `view.title`.
+    pub namespaced: bool,
+    /// Where the locale of a file is taken from
+    pub layout: LocaleLayout,
+    /// Load locales in their canonical form (see [`canonicalize_locale`]),
+    /// e.g. translations of `zh_CN.yml` are loaded as `zh-CN`
+    pub canonicalize_locales: bool,
+    /// Skip translations to be reviewed (see [`is_unreviewed`]) of `TODO.*` files, so they do not override
+    /// the reviewed ones, e.g. `STALE Bonjour` in `TODO.fr.yml` does not override `Bonjour` in `fr.yml`.
+    /// Translations of other files are reviewed, even if they start with a label.
+    pub skip_unreviewed: bool,
+}
+
 // Load locales into flatten key, value IndexMap
 pub fn load_locales<F: Fn(&str) -> bool>(
     locales_path: &str,
     ignore_if: F,
 ) -> IndexMap<String, IndexMap<String, String>> {
+    load_locales_with_options(locales_path, ignore_if, &LoadOptions::default())
+}
+
+/// Same as [`load_locales`], but with [`LoadOptions`]
+pub fn load_locales_with_options<F: Fn(&str) -> bool>(
+    locales_path: &str,
+    ignore_if: F,
+    options: &LoadOptions,
+) -> IndexMap<String, IndexMap<String, String>> {
+    load_locales_with_meta(locales_path, ignore_if, options).0
+}
+
+/// Same as [`load_locales_with_options`], and also return the `_meta` section of each locale
+/// (see [`LocaleInfo`]), e.g. `{"ar": {"direction": "rtl"}}`.
+#[allow(clippy::type_complexity)]
+pub fn load_locales_with_meta<F: Fn(&str) -> bool>(
+    locales_path: &str,
+    ignore_if: F,
+    options: &LoadOptions,
+) -> (
+    IndexMap<String, IndexMap<String, String>>,
+    IndexMap<String, IndexMap<String, String>>,
+) {
     let mut result: IndexMap<String, IndexMap<String, String>> = IndexMap::new();
+    let mut meta: IndexMap<String, IndexMap<String, String>> = IndexMap::new();
     let mut translations = IndexMap::new();
     let locales_path = match dunce::canonicalize(Path::new(locales_path)) {
         Ok(p) => p,
@@ -42,7 +187,7 @@ pub fn load_locales<F: Fn(&str) -> bool>(
             if is_debug() {
                 println!("cargo:i18n-error={}", e);
             }
-            return result;
+            return (result, meta);
         }
     };
     let locales_path = match locales_path.as_path().to_str() {
@@ -51,7 +196,7 @@ pub fn load_locales<F: Fn(&str) -> bool>(
             if is_debug() {
                 println!("cargo:i18n-error=could not convert path");
             }
-            return result;
+            return (result, meta);
         }
     };
 
@@ -66,7 +211,7 @@ pub fn load_locales<F: Fn(&str) -> bool>(
         if is_debug() {
             println!("cargo:i18n-error=path not exists: {}", locales_path);
         }
-        return result;
+        return (result, meta);
     }
 
     for entry in globwalk::glob(&path_pattern).expect("Failed to read glob pattern") {
@@ -79,11 +224,8 @@ pub fn load_locales<F: Fn(&str) -> bool>(
             continue;
         }
 
-        let locale = entry
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .and_then(|s| s.split('.').last())
-            .unwrap();
+        let (locale, namespace) = file_locale_namespace(Path::new(locales_path), &entry, options);
+        let locale = locale.as_str();
 
         let ext = entry.extension().and_then(|s| s.to_str()).unwrap();
 
@@ -95,7 +237,31 @@ pub fn load_locales<F: Fn(&str) -> bool>(
             .read_to_string(&mut content)
             .expect("Read file failed.");
 
-        let trs = parse_file(&content, ext, locale).expect("Parse file failed.");
+        // An empty file has no keys
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let (trs, file_meta) =
+            parse_file_with_meta(&content, ext, locale).expect("Parse file failed.");
+        let mut trs = check_locales(trs, &entry, options);
+        if options.skip_unreviewed && is_todo_file(&entry) {
+            trs.values_mut().for_each(remove_unreviewed);
+        }
+        let file_meta = check_locales(file_meta, &entry, options);
+        for (locale, value) in file_meta {
+            meta.entry(locale)
+                .or_default()
+                .extend(flatten_keys("", &value));
+        }
+
+        if options.namespaced && !namespace.is_empty() {
+            trs.values_mut().for_each(|value| {
+                let mut wrapped = serde_json::Map::new();
+                wrapped.insert(namespace.clone(), value.take());
+                *value = Value::Object(wrapped);
+            });
+        }
 
         trs.into_iter().for_each(|(k, new_value)| {
             translations
@@ -109,11 +275,150 @@ pub fn load_locales<F: Fn(&str) -> bool>(
         result.insert(locale.to_string(), flatten_keys("", trs));
     });
 
+    (result, meta)
+}
+
+/// Validate the locales of translations loaded from `path` as BCP-47 tags, and canonicalize them
+/// if enabled by `options`
+///
+/// Translations of an invalid locale, e.g. of a file named `user.en_US.utf8.yml`, are skipped with a warning.
+fn check_locales(trs: Translations, path: &Path, options: &LoadOptions) -> Translations {
+    let mut result = Translations::new();
+    for (locale, value) in trs {
+        let locale = match canonicalize_locale(&locale) {
+            Ok(canonical) if options.canonicalize_locales => canonical,
+            Ok(_) => locale,
+            Err(e) => {
+                eprintln!(
+                    "warning: {} in {}, its translations are skipped",
+                    e,
+                    path.display()
+                );
+                continue;
+            }
+        };
+        result
+            .entry(locale)
+            .and_modify(|old_value| merge_value(old_value, &value))
+            .or_insert(value);
+    }
     result
 }
 
+/// Get the namespace of a locale file from its name.
+///
+/// The namespace is the file stem without the `TODO.`/`REMOVED.` prefix and without
+/// the last (locale) segment, e.g. `view.en.yml` => `view`, `TODO.admin.users.fr.yml` => `admin.users`.
+/// Files without a prefix (`en.yml`, `app.yml`) have an empty namespace.
+pub fn file_namespace(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let mut segments = stem.split('.').collect::<Vec<_>>();
+    if matches!(segments.first(), Some(&"TODO") | Some(&"REMOVED")) {
+        segments.remove(0);
+    }
+    segments.pop();
+    segments.join(".")
+}
+
+/// Get locale and namespace of a locale file, according to the layout of `options`
+fn file_locale_namespace(
+    locales_path: &Path,
+    path: &Path,
+    options: &LoadOptions,
+) -> (String, String) {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let file_locale = stem.split('.').next_back().unwrap_or_default();
+
+    let dirs = path
+        .parent()
+        .and_then(|dir| dir.strip_prefix(locales_path).ok())
+        .map(|dir| {
+            dir.iter()
+                .filter_map(|s| s.to_str())
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    match (options.layout, dirs.split_first()) {
+        (LocaleLayout::Directory, Some((locale, sub_dirs))) => {
+            // `en/admin/users.yml` => `admin.users`, `en/TODO.view.en.yml` => `view`
+            let mut segments = stem.split('.').collect::<Vec<_>>();
+            if matches!(segments.first(), Some(&"TODO") | Some(&"REMOVED")) {
+                segments.remove(0);
+            }
+            if segments.last() == Some(&locale.as_str()) {
+                segments.pop();
+            }
+
+            let mut namespace = sub_dirs.to_vec();
+            namespace.extend(segments.into_iter().map(|s| s.to_string()));
+            (locale.clone(), namespace.join("."))
+        }
+        _ => (file_locale.to_string(), file_namespace(path)),
+    }
+}
+
+/// A locale file, with the locale and namespace its translations are loaded as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleFile {
+    /// Path of the file, relative to the locales path
+    pub path: PathBuf,
+    pub locale: String,
+    pub namespace: String,
+}
+
+/// Get all locale files in the given path
+pub fn locale_files(locales_path: &str, options: &LoadOptions) -> Vec<LocaleFile> {
+    let Ok(locales_path) = dunce::canonicalize(Path::new(locales_path)) else {
+        return vec![];
+    };
+    let path_pattern = format!("{}/**/*.{{yml,yaml,json,toml}}", locales_path.display());
+    let Ok(entries) = globwalk::glob(&path_pattern) else {
+        return vec![];
+    };
+    let mut files = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let (locale, namespace) = file_locale_namespace(&locales_path, entry.path(), options);
+            let path = entry.path().strip_prefix(&locales_path).ok()?.to_path_buf();
+            Some(LocaleFile {
+                path,
+                locale,
+                namespace,
+            })
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+/// Get namespaces of all locale files in the given path (see [`file_namespace`])
+pub fn file_namespaces(locales_path: &str, options: &LoadOptions) -> IndexSet<String> {
+    locale_files(locales_path, options)
+        .into_iter()
+        .map(|file| file.namespace)
+        .collect()
+}
+
 // Parse Translations from file to support multiple formats
+#[cfg(test)]
 fn parse_file(content: &str, ext: &str, locale: &str) -> Result<Translations, String> {
+    parse_file_with_meta(content, ext, locale).map(|(trs, _)| trs)
+}
+
+/// Parse Translations and the `_meta` section of each locale from file
+fn parse_file_with_meta(
+    content: &str,
+    ext: &str,
+    locale: &str,
+) -> Result<(Translations, Translations), String> {
     let result = match ext {
         "yml" | "yaml" => serde_yaml::from_str::<serde_json::Value>(content)
             .map_err(|err| format!("Invalid YAML format, {}", err)),
@@ -125,18 +430,28 @@ fn parse_file(content: &str, ext: &str, locale: &str) -> Result<Translations, St
     };
 
     match result {
-        Ok(mut v) => match get_version(&v) {
-            2 => {
-                if let Some(trs) = parse_file_v2("", &v) {
-                    return Ok(trs);
-                }
+        Ok(v) if !v.is_object() => Err("Invalid locale file format, expected a map of keys".into()),
+        Ok(mut v) => {
+            let meta = match v.as_object_mut().and_then(|o| o.remove(META_KEY)) {
+                Some(Value::Object(meta)) if get_version(&v) == 2 => meta.into_iter().collect(),
+                Some(meta) => Translations::from([(locale.to_string(), meta)]),
+                None => Translations::new(),
+            };
+
+            match get_version(&v) {
+                2 => {
+                    if let Some(trs) = parse_file_v2("", &v) {
+                        return Ok((trs, meta));
+                    }
+                    if !meta.is_empty() {
+                        return Ok((Translations::new(), meta));
+                    }
 
-                return Err("Invalid locale file format, please check the version field".into());
-            }
-            _ => {
-                return Ok(parse_file_v1(locale, &mut v));
+                    Err("Invalid locale file format, please check the version field".into())
+                }
+                _ => Ok((parse_file_v1(locale, &mut v), meta)),
             }
-        },
+        }
         Err(e) => Err(e),
     }
 }
@@ -152,7 +467,7 @@ fn parse_file_v1(locale: &str, data: &mut serde_json::Value) -> Translations {
     if let serde_json::Value::Object(messages) = data {
         messages.remove("_version");
     };
-    return Translations::from([(locale.to_string(), data.clone())]);
+    Translations::from([(locale.to_string(), data.clone())])
 }
 
 /// Locale file format v2
@@ -192,8 +507,8 @@ fn parse_file_v2(key_prefix: &str, data: &serde_json::Value) -> Option<Translati
                     // e.g:
                     //  en: Welcome
                     //  zh-CN: 欢迎
-                    if text.is_string() {
-                        let key = format_keys(&[&key_prefix, &key]);
+                    if text.is_string() || text.is_array() || select_arg(text).is_some() {
+                        let key = format_keys(&[key_prefix, key]);
                         let sub_trs = IndexMap::from([(key, text.clone())]);
                         let sub_value = serde_json::to_value(&sub_trs).unwrap();
 
@@ -206,8 +521,8 @@ fn parse_file_v2(key_prefix: &str, data: &serde_json::Value) -> Option<Translati
                     if text.is_object() {
                         // Parse the nested keys
                         // If the value is object (Map<locale, string>), iter them and convert them and insert into trs
-                        let key = format_keys(&[&key_prefix, &key]);
-                        if let Some(sub_trs) = parse_file_v2(&key, &value) {
+                        let key = format_keys(&[key_prefix, key]);
+                        if let Some(sub_trs) = parse_file_v2(&key, value) {
                             // println!("--------------- sub_trs:\n{:?}", sub_trs);
                             // Merge the sub_trs into trs
                             for (locale, sub_value) in sub_trs {
@@ -236,7 +551,7 @@ fn get_version(data: &serde_json::Value) -> usize {
         return version.as_u64().unwrap_or(1) as usize;
     }
 
-    return 1;
+    1
 }
 
 /// Join the keys with dot, if any key is empty, omit it.
@@ -256,6 +571,19 @@ fn flatten_keys(prefix: &str, trs: &Value) -> IndexMap<String, String> {
         serde_json::Value::String(s) => {
             v.insert(prefix, s.to_string());
         }
+        serde_json::Value::Object(o) if !prefix.is_empty() && select_arg(trs).is_some() => {
+            // Select map, the key itself is the `other` variant
+            let arg = select_arg(trs).unwrap_or_default();
+            let variants = &o[&format!("%{{{arg}}}")];
+            if let Some(other) = variants.get(SELECT_OTHER) {
+                v.extend(flatten_keys(&prefix, other));
+            }
+            if let Value::Object(variants) = variants {
+                for (variant, text) in variants {
+                    v.extend(flatten_keys(&variant_key(&prefix, arg, variant), text));
+                }
+            }
+        }
         serde_json::Value::Object(o) => {
             for (k, vv) in o {
                 let key = if prefix.is_empty() {
@@ -275,8 +603,10 @@ fn flatten_keys(prefix: &str, trs: &Value) -> IndexMap<String, String> {
         serde_json::Value::Number(s) => {
             v.insert(prefix, format!("{}", s));
         }
-        serde_json::Value::Array(_) => {
-            v.insert(prefix, "".into());
+        serde_json::Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                v.extend(flatten_keys(&format_keys(&[&prefix, &i.to_string()]), item));
+            }
         }
     }
 
@@ -285,7 +615,15 @@ fn flatten_keys(prefix: &str, trs: &Value) -> IndexMap<String, String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{merge_value, parse_file};
+    use super::{flatten_keys, is_unreviewed, merge_value, parse_file, parse_file_with_meta};
+
+    #[test]
+    fn test_is_unreviewed() {
+        assert!(is_unreviewed("FUZZY Enregistrer le fichier"));
+        assert!(is_unreviewed("STALE Bonjour"));
+        assert!(!is_unreviewed("FUZZYness"));
+        assert!(!is_unreviewed("Bonjour"));
+    }
 
     #[test]
     fn test_merge_value() {
@@ -324,6 +662,72 @@ mod tests {
         parse_file("", "yml", "en").expect_err("Should error");
     }
 
+    #[test]
+    fn test_flatten_keys_with_select() {
+        let value = serde_json::from_str::<serde_json::Value>(
+            r#"{"invite": {"%{gender}": {"male": "He", "female": "She", "other": "They"}}, "only": {"%{gender}": {"male": "He"}}}"#,
+        )
+        .unwrap();
+        let trs = flatten_keys("", &value);
+        assert_eq!(trs["invite"], "They");
+        assert_eq!(trs["invite.%{gender}.male"], "He");
+        assert_eq!(trs["invite.%{gender}.female"], "She");
+        assert_eq!(trs["invite.%{gender}.other"], "They");
+        assert_eq!(trs["only.%{gender}.male"], "He");
+        assert!(!trs.contains_key("only"));
+
+        let content = "_version: 2\ninvite:\n  en:\n    \"%{gender}\":\n      male: He\n      other: They\n  zh-CN: 他们\n";
+        let trs = parse_file(content, "yml", "").unwrap();
+        let en = flatten_keys("", &trs["en"]);
+        assert_eq!(en["invite"], "They");
+        assert_eq!(en["invite.%{gender}.male"], "He");
+        assert_eq!(flatten_keys("", &trs["zh-CN"])["invite"], "他们");
+    }
+
+    #[test]
+    fn test_parse_file_with_meta() {
+        let content = "_meta:\n  direction: rtl\n  native-name: العربية\nhello: مرحبا\n";
+        let (trs, meta) = parse_file_with_meta(content, "yml", "ar").unwrap();
+        assert_eq!(
+            flatten_keys("", &trs["ar"]).keys().collect::<Vec<_>>(),
+            ["hello"]
+        );
+        assert_eq!(flatten_keys("", &meta["ar"])["direction"], "rtl");
+
+        let content = "_version: 2\n_meta:\n  ar:\n    direction: rtl\n  en:\n    english-name: English\nhello:\n  en: Hello\n  ar: مرحبا\n";
+        let (trs, meta) = parse_file_with_meta(content, "yml", "").unwrap();
+        assert_eq!(
+            flatten_keys("", &trs["en"]).keys().collect::<Vec<_>>(),
+            ["hello"]
+        );
+        assert_eq!(flatten_keys("", &meta["ar"])["direction"], "rtl");
+        assert_eq!(flatten_keys("", &meta["en"])["english-name"], "English");
+
+        let content = "_version: 2\n_meta:\n  ar:\n    direction: rtl\n";
+        let (trs, meta) = parse_file_with_meta(content, "yml", "").unwrap();
+        assert!(trs.is_empty());
+        assert_eq!(meta.len(), 1);
+    }
+
+    #[test]
+    fn test_flatten_keys_with_arrays() {
+        let value = serde_json::json!({
+            "days": ["Mon", "Tue"],
+            "steps": [{ "title": "Install" }, { "title": "Run" }],
+            "empty": [],
+        });
+        let trs = flatten_keys("", &value);
+        assert_eq!(trs["days.0"], "Mon");
+        assert_eq!(trs["days.1"], "Tue");
+        assert_eq!(trs["steps.0.title"], "Install");
+        assert_eq!(trs["steps.1.title"], "Run");
+        assert!(!trs.contains_key("empty"));
+
+        let content = "_version: 2\ndays:\n  en: [Mon, Tue]\n  fr: [Lun, Mar]";
+        let trs = parse_file(content, "yml", "app").expect("Should ok");
+        assert_eq!(flatten_keys("", &trs["fr"])["days.1"], "Mar");
+    }
+
     #[test]
     fn test_parse_file_in_json() {
         let content = r#"
@@ -348,6 +752,96 @@ mod tests {
         assert_eq!(trs["en"]["bar"], "Bar");
     }
 
+    #[test]
+    fn test_check_locales() {
+        use super::{check_locales, LoadOptions};
+        use std::path::Path;
+
+        let trs = parse_file("foo: Foo", "yml", "EN_us").unwrap();
+        let path = Path::new("locales/EN_us.yml");
+        assert!(check_locales(trs.clone(), path, &LoadOptions::default()).contains_key("EN_us"));
+
+        let options = LoadOptions {
+            canonicalize_locales: true,
+            ..Default::default()
+        };
+        let content = "_version: 2\nfoo:\n  zh_CN: 甲\n  zh-CN: 乙\nbar:\n  zh_CN: 丙";
+        let trs = check_locales(parse_file(content, "yml", "app").unwrap(), path, &options);
+        assert_eq!(trs.keys().collect::<Vec<_>>(), ["zh-CN"]);
+        assert_eq!(trs["zh-CN"]["foo"], "乙");
+        assert_eq!(trs["zh-CN"]["bar"], "丙");
+    }
+
+    #[test]
+    fn test_check_locales_invalid() {
+        use super::{check_locales, LoadOptions};
+        use std::path::Path;
+
+        let trs = parse_file("foo: Foo", "yml", "yaml").unwrap();
+        let path = Path::new("locales/user.en.yaml.yml");
+        assert!(check_locales(trs, path, &LoadOptions::default()).is_empty());
+
+        let content = "_version: 2\nfoo:\n  en: Foo\n  yaml: Foo";
+        let trs = check_locales(
+            parse_file(content, "yml", "app").unwrap(),
+            path,
+            &LoadOptions::default(),
+        );
+        assert_eq!(trs.keys().collect::<Vec<_>>(), ["en"]);
+    }
+
+    #[test]
+    fn test_file_namespace() {
+        use super::file_namespace;
+        use std::path::Path;
+
+        assert_eq!(file_namespace(Path::new("locales/en.yml")), "");
+        assert_eq!(file_namespace(Path::new("locales/app.yml")), "");
+        assert_eq!(file_namespace(Path::new("locales/view.en.yml")), "view");
+        assert_eq!(file_namespace(Path::new("TODO.view.en.yml")), "view");
+        assert_eq!(file_namespace(Path::new("REMOVED.en.yml")), "");
+        assert_eq!(
+            file_namespace(Path::new("admin.users.zh-CN.json")),
+            "admin.users"
+        );
+    }
+
+    #[test]
+    fn test_file_locale_namespace() {
+        use super::{file_locale_namespace, LoadOptions, LocaleLayout};
+        use std::path::Path;
+
+        let root = Path::new("/app/locales");
+        let file = LoadOptions::default();
+        let directory = LoadOptions {
+            layout: LocaleLayout::Directory,
+            ..Default::default()
+        };
+        let info = |path: &str, options: &LoadOptions| {
+            file_locale_namespace(root, &root.join(path), options)
+        };
+
+        assert_eq!(info("view.en.yml", &file), ("en".into(), "view".into()));
+        assert_eq!(info("fr/view.yml", &file), ("view".into(), "".into()));
+        assert_eq!(
+            info("view.en.yml", &directory),
+            ("en".into(), "view".into())
+        );
+        assert_eq!(
+            info("fr/view.yml", &directory),
+            ("fr".into(), "view".into())
+        );
+        assert_eq!(info("fr/fr.yml", &directory), ("fr".into(), "".into()));
+        assert_eq!(
+            info("fr/TODO.view.fr.yml", &directory),
+            ("fr".into(), "view".into())
+        );
+        assert_eq!(
+            info("zh-CN/admin/users.json", &directory),
+            ("zh-CN".into(), "admin.users".into())
+        );
+    }
+
     #[test]
     fn test_get_version() {
         let json = serde_yaml::from_str::<serde_json::Value>("_version: 2").unwrap();
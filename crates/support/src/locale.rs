@@ -0,0 +1,286 @@
+//! Detect the locale of the system and negotiate it against the available locales.
+
+/// Normalize a POSIX locale to a locale tag, e.g. `pt_BR.UTF-8@euro` to `pt-BR`
+///
+/// Returns `None` for the `C` and `POSIX` locales, which have no language.
+pub fn normalize_posix_locale(locale: &str) -> Option<String> {
+    let locale = locale.trim();
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+
+    Some(locale.replace('_', "-"))
+}
+
+/// Deprecated language subtags and their replacements
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("in", "id"),
+    ("iw", "he"),
+    ("ji", "yi"),
+    ("jw", "jv"),
+    ("mo", "ro"),
+];
+
+/// Validate a locale as a BCP-47 language tag and return its canonical form
+///
+/// `_` is accepted as separator, subtags are cased as recommended (`zh-Hant-TW`) and deprecated
+/// languages are replaced, e.g. `EN_us` => `en-US`, `iw` => `he`, `pt_BR.UTF-8` is an error.
+pub fn canonicalize_locale(locale: &str) -> Result<String, String> {
+    let invalid = |reason: &str| Err(format!("Invalid locale `{locale}`, {reason}"));
+    let is_alpha = |s: &str, len: std::ops::RangeInclusive<usize>| {
+        len.contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphabetic())
+    };
+    let is_alnum = |s: &str, len: std::ops::RangeInclusive<usize>| {
+        len.contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric())
+    };
+
+    let subtags: Vec<&str> = locale.split(['-', '_']).collect();
+    let mut subtags = subtags.into_iter().peekable();
+    let mut result: Vec<String> = vec![];
+
+    // language, or only private use (`x-klingon`)
+    match subtags.peek().copied() {
+        Some(private_use) if private_use.eq_ignore_ascii_case("x") => {}
+        Some(language) if is_alpha(language, 2..=3) || is_alpha(language, 5..=8) => {
+            subtags.next();
+            let language = language.to_ascii_lowercase();
+            let language = LANGUAGE_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == language)
+                .map(|(_, replacement)| replacement.to_string())
+                .unwrap_or(language);
+            result.push(language);
+
+            // extended language subtags, e.g. `zh-yue`
+            while result.len() < 4 && subtags.peek().is_some_and(|s| is_alpha(s, 3..=3)) {
+                result.push(subtags.next().unwrap().to_ascii_lowercase());
+            }
+            if let Some(script) = subtags.next_if(|s| is_alpha(s, 4..=4)) {
+                let script = script.to_ascii_lowercase();
+                result.push(script[..1].to_ascii_uppercase() + &script[1..]);
+            }
+            if let Some(region) = subtags.next_if(|s| {
+                is_alpha(s, 2..=2) || (s.len() == 3 && s.chars().all(|c| c.is_ascii_digit()))
+            }) {
+                result.push(region.to_ascii_uppercase());
+            }
+            while let Some(variant) = subtags.next_if(|s| {
+                is_alnum(s, 5..=8)
+                    || (s.len() == 4
+                        && s.starts_with(|c: char| c.is_ascii_digit())
+                        && is_alnum(s, 4..=4))
+            }) {
+                result.push(variant.to_ascii_lowercase());
+            }
+        }
+        _ => return invalid("expected a language like `en` or `zh-CN`"),
+    }
+
+    // extensions (`u-ca-buddhist`) and private use (`x-private`)
+    while let Some(singleton) = subtags.next() {
+        if !is_alnum(singleton, 1..=1) {
+            return invalid(&format!("unexpected subtag `{singleton}`"));
+        }
+        let singleton = singleton.to_ascii_lowercase();
+        let private_use = singleton == "x";
+        result.push(singleton);
+
+        let start = result.len();
+        while let Some(subtag) = subtags.next_if(|s| {
+            if private_use {
+                is_alnum(s, 1..=8)
+            } else {
+                is_alnum(s, 2..=8)
+            }
+        }) {
+            result.push(subtag.to_ascii_lowercase());
+        }
+        if result.len() == start {
+            return invalid("expected subtags after a singleton");
+        }
+    }
+
+    Ok(result.join("-"))
+}
+
+/// Preferred locales of the system, most preferred first
+///
+/// Like gettext, the `LANGUAGE` priority list (e.g. `fr:de`) comes before the locale of
+/// `LC_ALL`, `LC_MESSAGES` or `LANG` (the first one set), and is ignored if that locale is `C`.
+pub fn system_locales() -> Vec<String> {
+    system_locales_from(|name| std::env::var(name).ok())
+}
+
+fn system_locales_from(var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    let locale = var("LC_ALL")
+        .or_else(|| var("LC_MESSAGES"))
+        .or_else(|| var("LANG"));
+    let locale = match locale {
+        Some(locale) => match normalize_posix_locale(&locale) {
+            Some(locale) => Some(locale),
+            None => return vec![],
+        },
+        None => None,
+    };
+
+    let mut locales: Vec<String> = var("LANGUAGE")
+        .unwrap_or_default()
+        .split(':')
+        .filter_map(normalize_posix_locale)
+        .collect();
+    locales.extend(locale);
+
+    let mut unique = vec![];
+    for locale in locales {
+        if !unique.contains(&locale) {
+            unique.push(locale);
+        }
+    }
+    unique
+}
+
+/// Find the best match of the requested locales in the available locales
+///
+/// For each requested locale, most preferred first, tries an exact match (ignoring case),
+/// then its parents (`zh-Hant-TW`, `zh-Hant`, `zh`), then any locale of the same language.
+pub fn negotiate_locale<'a, S: AsRef<str>>(
+    requested: &[S],
+    available: &[&'a str],
+) -> Option<&'a str> {
+    let find = |locale: &str| {
+        available
+            .iter()
+            .find(|l| l.replace('_', "-").eq_ignore_ascii_case(locale))
+            .copied()
+    };
+
+    for locale in requested {
+        let mut locale = locale.as_ref().replace('_', "-");
+        if let Some(found) = find(&locale) {
+            return Some(found);
+        }
+        while let Some((parent, _)) = locale.rsplit_once('-') {
+            locale = parent.to_string();
+            if let Some(found) = find(&locale) {
+                return Some(found);
+            }
+        }
+        let same_language = available.iter().find(|l| {
+            l.split(['-', '_'])
+                .next()
+                .unwrap_or_default()
+                .eq_ignore_ascii_case(&locale)
+        });
+        if let Some(found) = same_language {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_posix_locale() {
+        assert_eq!(
+            normalize_posix_locale("pt_BR.UTF-8@euro").as_deref(),
+            Some("pt-BR")
+        );
+        assert_eq!(
+            normalize_posix_locale("de_DE@euro").as_deref(),
+            Some("de-DE")
+        );
+        assert_eq!(normalize_posix_locale("fr").as_deref(), Some("fr"));
+        assert_eq!(normalize_posix_locale("C.UTF-8"), None);
+        assert_eq!(normalize_posix_locale("POSIX"), None);
+        assert_eq!(normalize_posix_locale(""), None);
+    }
+
+    #[test]
+    fn test_canonicalize_locale() {
+        let canonical = |locale| canonicalize_locale(locale).unwrap();
+        assert_eq!(canonical("en"), "en");
+        assert_eq!(canonical("EN_us"), "en-US");
+        assert_eq!(canonical("zh_CN"), "zh-CN");
+        assert_eq!(canonical("zh-hant-tw"), "zh-Hant-TW");
+        assert_eq!(canonical("es-419"), "es-419");
+        assert_eq!(canonical("iw"), "he");
+        assert_eq!(canonical("zh-yue-HK"), "zh-yue-HK");
+        assert_eq!(canonical("de-CH-1996"), "de-CH-1996");
+        assert_eq!(canonical("sl-rozaj-biske"), "sl-rozaj-biske");
+        assert_eq!(canonical("th-TH-u-NU-thai"), "th-TH-u-nu-thai");
+        assert_eq!(canonical("en-x-Pirate"), "en-x-pirate");
+        assert_eq!(canonical("x-klingon"), "x-klingon");
+
+        for locale in [
+            "",
+            "e",
+            "yaml",
+            "en-",
+            "en--US",
+            "pt_BR.UTF-8",
+            "C",
+            "en-US-u",
+            "en-a-b",
+            "123",
+        ] {
+            assert!(canonicalize_locale(locale).is_err(), "{locale}");
+        }
+    }
+
+    #[test]
+    fn test_system_locales() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            system_locales_from(move |name| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string())
+            })
+        };
+
+        assert_eq!(
+            env(&[("LANG", "pt_BR.UTF-8"), ("LANGUAGE", "fr_FR:de")]),
+            vec!["fr-FR", "de", "pt-BR"]
+        );
+        assert_eq!(
+            env(&[
+                ("LANG", "en_US.UTF-8"),
+                ("LC_MESSAGES", "de_DE"),
+                ("LC_ALL", "")
+            ]),
+            vec!["de-DE"]
+        );
+        assert_eq!(
+            env(&[("LC_ALL", "zh_CN"), ("LC_MESSAGES", "de_DE")]),
+            vec!["zh-CN"]
+        );
+        assert_eq!(
+            env(&[("LC_ALL", "C"), ("LANGUAGE", "fr")]),
+            Vec::<String>::new()
+        );
+        assert_eq!(env(&[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_negotiate_locale() {
+        let available = ["en", "pt-BR", "zh-CN", "zh-Hant"];
+        assert_eq!(negotiate_locale(&["pt-br"], &available), Some("pt-BR"));
+        assert_eq!(negotiate_locale(&["pt-PT"], &available), Some("pt-BR"));
+        assert_eq!(
+            negotiate_locale(&["zh-Hant-TW"], &available),
+            Some("zh-Hant")
+        );
+        assert_eq!(negotiate_locale(&["en-US"], &available), Some("en"));
+        assert_eq!(
+            negotiate_locale(&["fr", "zh_CN"], &available),
+            Some("zh-CN")
+        );
+        assert_eq!(negotiate_locale(&["fr"], &available), None);
+        assert_eq!(negotiate_locale::<&str>(&[], &available), None);
+    }
+}
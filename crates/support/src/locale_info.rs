@@ -0,0 +1,204 @@
+use crate::plural::{plural_category, PluralCategory, PluralOperands};
+use serde_derive::{Deserialize, Serialize};
+
+/// Key of the reserved section of locale files for [`LocaleInfo`]
+///
+/// ```yml
+/// _meta:
+///   direction: rtl
+///   native-name: العربية
+///   english-name: Arabic
+///   plural-rules: ar
+/// ```
+pub const META_KEY: &str = "_meta";
+
+/// Text direction of a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Ltr => "ltr",
+            Direction::Rtl => "rtl",
+        }
+    }
+}
+
+/// Metadata of a locale
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LocaleInfo {
+    pub direction: Direction,
+    /// Name of the locale in its own language, e.g. `Deutsch`
+    pub native_name: String,
+    /// Name of the locale in English, e.g. `German`
+    pub english_name: String,
+    /// Language whose plural rules are used, e.g. `ru`
+    pub plural_rules: String,
+}
+
+/// Built-in names of locales, `(locale, English name, native name)`, locales of the same language
+/// without their own entry use the entry of the language.
+const LOCALES: &[(&str, &str, &str)] = &[
+    ("ar", "Arabic", "العربية"),
+    ("bg", "Bulgarian", "Български"),
+    ("bn", "Bengali", "বাংলা"),
+    ("ca", "Catalan", "Català"),
+    ("cs", "Czech", "Čeština"),
+    ("da", "Danish", "Dansk"),
+    ("de", "German", "Deutsch"),
+    ("el", "Greek", "Ελληνικά"),
+    ("en", "English", "English"),
+    (
+        "en-GB",
+        "English (United Kingdom)",
+        "English (United Kingdom)",
+    ),
+    (
+        "en-US",
+        "English (United States)",
+        "English (United States)",
+    ),
+    ("es", "Spanish", "Español"),
+    ("et", "Estonian", "Eesti"),
+    ("fa", "Persian", "فارسی"),
+    ("fi", "Finnish", "Suomi"),
+    ("fr", "French", "Français"),
+    ("he", "Hebrew", "עברית"),
+    ("hi", "Hindi", "हिन्दी"),
+    ("hr", "Croatian", "Hrvatski"),
+    ("hu", "Hungarian", "Magyar"),
+    ("id", "Indonesian", "Indonesia"),
+    ("it", "Italian", "Italiano"),
+    ("ja", "Japanese", "日本語"),
+    ("ko", "Korean", "한국어"),
+    ("lt", "Lithuanian", "Lietuvių"),
+    ("lv", "Latvian", "Latviešu"),
+    ("ms", "Malay", "Melayu"),
+    ("nb", "Norwegian Bokmål", "Norsk bokmål"),
+    ("nl", "Dutch", "Nederlands"),
+    ("pl", "Polish", "Polski"),
+    ("ps", "Pashto", "پښتو"),
+    ("pt", "Portuguese", "Português"),
+    ("pt-BR", "Portuguese (Brazil)", "Português (Brasil)"),
+    ("ro", "Romanian", "Română"),
+    ("ru", "Russian", "Русский"),
+    ("sk", "Slovak", "Slovenčina"),
+    ("sl", "Slovenian", "Slovenščina"),
+    ("sv", "Swedish", "Svenska"),
+    ("th", "Thai", "ไทย"),
+    ("tr", "Turkish", "Türkçe"),
+    ("uk", "Ukrainian", "Українська"),
+    ("ur", "Urdu", "اردو"),
+    ("vi", "Vietnamese", "Tiếng Việt"),
+    ("yi", "Yiddish", "ייִדיש"),
+    ("zh", "Chinese", "中文"),
+    ("zh-CN", "Chinese (Simplified)", "简体中文"),
+    ("zh-HK", "Chinese (Hong Kong)", "繁體中文（香港）"),
+    ("zh-TW", "Chinese (Traditional)", "繁體中文"),
+];
+
+/// Languages written from right to left
+const RTL_LANGUAGES: &[&str] = &["ar", "ckb", "dv", "fa", "he", "ps", "sd", "ug", "ur", "yi"];
+
+impl LocaleInfo {
+    /// Built-in metadata of the locale, unknown locales are left-to-right and named by themselves
+    pub fn new(locale: &str) -> Self {
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        let names = LOCALES
+            .iter()
+            .find(|(l, _, _)| l.eq_ignore_ascii_case(&locale.replace('_', "-")))
+            .or_else(|| LOCALES.iter().find(|(l, _, _)| *l == language));
+        let (english_name, native_name) = match names {
+            Some((_, english_name, native_name)) => {
+                (english_name.to_string(), native_name.to_string())
+            }
+            None => (locale.to_string(), locale.to_string()),
+        };
+
+        Self {
+            direction: if RTL_LANGUAGES.contains(&language) {
+                Direction::Rtl
+            } else {
+                Direction::Ltr
+            },
+            native_name,
+            english_name,
+            plural_rules: language.to_string(),
+        }
+    }
+
+    /// Override by the `_meta` section of locale files, unknown keys and invalid values are ignored
+    pub fn with_meta<'a>(mut self, meta: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        for (key, value) in meta {
+            match key {
+                "direction" => match value {
+                    "ltr" => self.direction = Direction::Ltr,
+                    "rtl" => self.direction = Direction::Rtl,
+                    _ => {}
+                },
+                "native-name" => self.native_name = value.to_string(),
+                "english-name" => self.english_name = value.to_string(),
+                "plural-rules" => self.plural_rules = value.to_string(),
+                _ => {}
+            }
+        }
+        self
+    }
+
+    pub fn is_rtl(&self) -> bool {
+        self.direction == Direction::Rtl
+    }
+
+    /// Plural category of a number by the plural rules of the locale
+    pub fn plural_category(&self, n: &PluralOperands) -> PluralCategory {
+        plural_category(&self.plural_rules, n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_info() {
+        let info = LocaleInfo::new("ar-EG");
+        assert!(info.is_rtl());
+        assert_eq!(info.english_name, "Arabic");
+        assert_eq!(info.native_name, "العربية");
+        assert_eq!(info.plural_rules, "ar");
+
+        let info = LocaleInfo::new("zh_CN");
+        assert_eq!(info.direction, Direction::Ltr);
+        assert_eq!(info.native_name, "简体中文");
+        assert_eq!(info.plural_category(&1i64.into()), PluralCategory::Other);
+
+        let info = LocaleInfo::new("tlh");
+        assert_eq!(info.english_name, "tlh");
+        assert_eq!(info.native_name, "tlh");
+    }
+
+    #[test]
+    fn test_with_meta() {
+        let info = LocaleInfo::new("tlh").with_meta([
+            ("direction", "rtl"),
+            ("native-name", "tlhIngan Hol"),
+            ("english-name", "Klingon"),
+            ("plural-rules", "ru"),
+            ("unknown", "value"),
+        ]);
+        assert!(info.is_rtl());
+        assert_eq!(info.native_name, "tlhIngan Hol");
+        assert_eq!(info.english_name, "Klingon");
+        assert_eq!(info.plural_category(&3i64.into()), PluralCategory::Few);
+
+        let info = LocaleInfo::new("ar").with_meta([("direction", "invalid")]);
+        assert!(info.is_rtl());
+    }
+}
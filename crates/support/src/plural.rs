@@ -0,0 +1,204 @@
+//! CLDR plural rules of common languages, for selecting the plural form of a number.
+
+/// Plural category of a number, see <https://cldr.unicode.org/index/cldr-spec/plural-rules>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Operands of a number for plural rules
+///
+/// - `n`: absolute value
+/// - `i`: integer digits
+/// - `v`: number of visible fraction digits (with trailing zeros)
+/// - `f`: visible fraction digits (with trailing zeros)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PluralOperands {
+    pub n: f64,
+    pub i: u64,
+    pub v: usize,
+    pub f: u64,
+}
+
+impl PluralOperands {
+    /// Operands of a number written as text, e.g. `1.50` has 2 visible fraction digits
+    pub fn parse(number: &str) -> Option<Self> {
+        let number = number.trim().trim_start_matches(['-', '+']);
+        let (int, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if int.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        if !int
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        Some(Self {
+            n: number.parse().ok()?,
+            i: if int.is_empty() { 0 } else { int.parse().ok()? },
+            v: fraction.len(),
+            f: if fraction.is_empty() {
+                0
+            } else {
+                fraction.parse().ok()?
+            },
+        })
+    }
+}
+
+impl From<i64> for PluralOperands {
+    fn from(n: i64) -> Self {
+        Self {
+            n: n.unsigned_abs() as f64,
+            i: n.unsigned_abs(),
+            v: 0,
+            f: 0,
+        }
+    }
+}
+
+impl From<f64> for PluralOperands {
+    fn from(n: f64) -> Self {
+        Self::parse(&n.to_string()).unwrap_or_default()
+    }
+}
+
+/// Language of a locale, e.g. `pt` of `pt-BR`
+fn language(locale: &str) -> String {
+    locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Cardinal plural category (`1 file`, `2 files`) of a number in the locale
+pub fn plural_category(locale: &str, n: &PluralOperands) -> PluralCategory {
+    use PluralCategory::*;
+
+    let (i, v) = (n.i, n.v);
+    let (i10, i100) = (i % 10, i % 100);
+    let integer = n.n.fract() == 0.0;
+
+    match language(locale).as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "my" | "lo" | "km" => Other,
+        "fr" | "hy" | "pt" if i <= 1 => One,
+        "fr" | "hy" | "pt" => Other,
+        "ru" | "uk" | "be" if v == 0 && i10 == 1 && i100 != 11 => One,
+        "ru" | "uk" | "be" if v == 0 && (2..=4).contains(&i10) && !(12..=14).contains(&i100) => Few,
+        "ru" | "uk" | "be" if v == 0 => Many,
+        "ru" | "uk" | "be" => Other,
+        "pl" if i == 1 && v == 0 => One,
+        "pl" if v == 0 && (2..=4).contains(&i10) && !(12..=14).contains(&i100) => Few,
+        "pl" if v == 0 => Many,
+        "pl" => Other,
+        "cs" | "sk" if i == 1 && v == 0 => One,
+        "cs" | "sk" if (2..=4).contains(&i) && v == 0 => Few,
+        "cs" | "sk" if v != 0 => Many,
+        "cs" | "sk" => Other,
+        "ar" if n.n == 0.0 => Zero,
+        "ar" if n.n == 1.0 => One,
+        "ar" if n.n == 2.0 => Two,
+        "ar" if integer && (3..=10).contains(&(n.n as u64 % 100)) => Few,
+        "ar" if integer && (11..=99).contains(&(n.n as u64 % 100)) => Many,
+        "ar" => Other,
+        "he" if i == 1 && v == 0 => One,
+        "he" if i == 2 && v == 0 => Two,
+        "he" => Other,
+        _ if i == 1 && v == 0 => One,
+        _ => Other,
+    }
+}
+
+/// Ordinal plural category (`1st`, `2nd`, `3rd`, `4th`) of a number in the locale
+pub fn ordinal_category(locale: &str, n: &PluralOperands) -> PluralCategory {
+    use PluralCategory::*;
+
+    if n.n.fract() != 0.0 {
+        return Other;
+    }
+    let n = n.n as u64;
+
+    match language(locale).as_str() {
+        "en" if n % 10 == 1 && n % 100 != 11 => One,
+        "en" if n % 10 == 2 && n % 100 != 12 => Two,
+        "en" if n % 10 == 3 && n % 100 != 13 => Few,
+        "fr" if n == 1 => One,
+        _ => Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(locale: &str, n: &str) -> &'static str {
+        plural_category(locale, &PluralOperands::parse(n).unwrap()).as_str()
+    }
+
+    #[test]
+    fn test_plural_category() {
+        assert_eq!(category("en", "1"), "one");
+        assert_eq!(category("en", "1.0"), "other");
+        assert_eq!(category("en-US", "2"), "other");
+        assert_eq!(category("zh-CN", "1"), "other");
+        assert_eq!(category("fr", "0"), "one");
+        assert_eq!(category("fr", "1.5"), "one");
+        assert_eq!(category("fr", "2"), "other");
+        assert_eq!(category("ru", "1"), "one");
+        assert_eq!(category("ru", "21"), "one");
+        assert_eq!(category("ru", "11"), "many");
+        assert_eq!(category("ru", "3"), "few");
+        assert_eq!(category("ru", "5"), "many");
+        assert_eq!(category("ru", "1.5"), "other");
+        assert_eq!(category("pl", "22"), "few");
+        assert_eq!(category("pl", "21"), "many");
+        assert_eq!(category("cs", "3"), "few");
+        assert_eq!(category("ar", "0"), "zero");
+        assert_eq!(category("ar", "2"), "two");
+        assert_eq!(category("ar", "103"), "few");
+        assert_eq!(category("ar", "111"), "many");
+        assert_eq!(category("ar", "100"), "other");
+    }
+
+    #[test]
+    fn test_ordinal_category() {
+        let category = |n: i64| ordinal_category("en", &n.into()).as_str();
+        assert_eq!(category(1), "one");
+        assert_eq!(category(2), "two");
+        assert_eq!(category(3), "few");
+        assert_eq!(category(4), "other");
+        assert_eq!(category(11), "other");
+        assert_eq!(category(22), "two");
+    }
+
+    #[test]
+    fn test_plural_operands() {
+        let n = PluralOperands::parse("1.50").unwrap();
+        assert_eq!((n.n, n.i, n.v, n.f), (1.5, 1, 2, 50));
+        assert_eq!(PluralOperands::from(-3i64).i, 3);
+        assert_eq!(PluralOperands::from(2.5).v, 1);
+        assert!(PluralOperands::parse("abc").is_none());
+    }
+}
@@ -1,8 +1,8 @@
 #[macro_use]
 extern crate rust_i18n;
 
-// Init translations for current crate.
-i18n!("../locales");
+// Init translations for current crate, the load path is inherited from the workspace config.
+i18n!();
 
 fn get_text() -> String {
     t!("hello")
@@ -16,4 +16,14 @@ mod tests {
         rust_i18n::set_locale("fr");
         assert_eq!("Foo - Bonjour, monde!", get_info());
     }
+
+    #[test]
+    fn test_config() {
+        assert_eq!(rust_i18n::default_locale!(), "en");
+        assert_eq!(rust_i18n::available_locales!(), vec!["en", "fr"]);
+        assert_eq!(
+            rust_i18n::t!("hello_fallback", locale = "fr"),
+            "This hello_fallback is fallback to en."
+        );
+    }
 }
@@ -2,7 +2,8 @@ use rust_i18n::t;
 
 mod info;
 
-rust_i18n::i18n!("locales", fallback = "en");
+// Load path and fallback are taken from `[package.metadata.i18n]` in Cargo.toml
+rust_i18n::i18n!();
 
 pub fn t(key: &str) -> String {
     t!(key)
@@ -1,4 +1,5 @@
-rust_i18n::i18n!(backend = my_i18n::I18nBackend);
+// Own locales of the app take precedence over the shared locales of the workspace
+rust_i18n::i18n!(["locales", "../locales"], fallback = "en");
 
 #[cfg(test)]
 mod tests {
@@ -14,5 +15,7 @@ mod tests {
             t!("welcome", locale = "zh-CN"),
             "Rust I18n 示例，用于在整个工作区中共享本地化。"
         );
+        assert_eq!(t!("welcome", locale = "zh-HK"), "歡迎使用 My App 1。");
+        assert_eq!(t!("title", locale = "en"), "My App 1");
     }
 }
@@ -1,4 +1,5 @@
-rust_i18n::i18n!(backend = my_i18n::I18nBackend);
+// Shared locales of the workspace
+rust_i18n::i18n!("../locales", fallback = "en");
 
 #[cfg(test)]
 mod tests {
@@ -14,5 +15,9 @@ mod tests {
             t!("welcome", locale = "zh-CN"),
             "Rust I18n 示例，用于在整个工作区中共享本地化。"
         );
+        assert_eq!(
+            t!("welcome", locale = "fr"),
+            "Rust I18n Example for share locales in entire workspace."
+        );
     }
 }
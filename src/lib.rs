@@ -2,19 +2,36 @@
 #![doc = include_str!("../README.md")]
 
 use once_cell::sync::Lazy;
-use std::sync::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 
 #[doc(hidden)]
 pub use once_cell;
 pub use rust_i18n_macro::{i18n, ToStringI18N};
-pub use rust_i18n_support::{Backend, BackendExt, SimpleBackend};
+pub use rust_i18n_support::locale::{negotiate_locale, normalize_posix_locale, system_locales};
+pub use rust_i18n_support::{
+    context_key, variant_key, Backend, BackendExt, Direction, LazyBackend, LocaleInfo,
+    SimpleBackend,
+};
+pub use rust_i18n_support::{icu, plural};
 
 static CURRENT_LOCALE: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new(String::from("en")));
 
-/// Set current locale
+/// Set current locale, observers are notified if the locale is changed
 pub fn set_locale(locale: &str) {
-    let mut current_locale = CURRENT_LOCALE.write().unwrap();
-    *current_locale = locale.to_string();
+    let previous = {
+        let mut current_locale = CURRENT_LOCALE.write().unwrap();
+        if *current_locale == locale {
+            return;
+        }
+        std::mem::replace(&mut *current_locale, locale.to_string())
+    };
+
+    notify(LocaleEvent::LocaleChanged {
+        from: previous,
+        to: locale.to_string(),
+    });
 }
 
 /// Get current locale
@@ -22,6 +39,179 @@ pub fn locale() -> String {
     CURRENT_LOCALE.read().unwrap().to_string()
 }
 
+/// Set current locale to the best match of [`system_locales`] in `available`, or to `default`
+///
+/// Returns the locale that was set, see [`set_system_locale!`] for the locales of the crate.
+pub fn set_locale_from_system(available: &[&str], default: &str) -> String {
+    let locale = negotiate_locale(&system_locales(), available)
+        .unwrap_or(default)
+        .to_string();
+    set_locale(&locale);
+    locale
+}
+
+/// A change of the current locale or of translations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocaleEvent {
+    /// The current locale is changed by [`set_locale`]
+    LocaleChanged { from: String, to: String },
+    /// Translations are changed, by [`register_backend`], [`unload_locale!`] or [`notify_reload`]
+    Reloaded,
+}
+
+/// Id of an observer added by [`on_locale_change`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(usize);
+
+type Observer = Arc<dyn Fn(&LocaleEvent) + Send + Sync>;
+
+static LOCALE_EPOCH: AtomicU64 = AtomicU64::new(0);
+static LOCALE_OBSERVERS: Lazy<RwLock<Vec<(ObserverId, Observer)>>> =
+    Lazy::new(|| RwLock::new(vec![]));
+static NEXT_OBSERVER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the epoch of the current locale and translations, which is incremented on every [`LocaleEvent`]
+///
+/// Compare it with the epoch of cached texts to know whether they need to be translated again.
+///
+/// ```ignore
+/// if self.labels_epoch != rust_i18n::locale_epoch() {
+///     self.labels = Labels::new();
+///     self.labels_epoch = rust_i18n::locale_epoch();
+/// }
+/// ```
+pub fn locale_epoch() -> u64 {
+    LOCALE_EPOCH.load(Ordering::Acquire)
+}
+
+/// Call `observer` on every [`LocaleEvent`], until it is removed by [`remove_locale_observer`]
+///
+/// ```ignore
+/// let id = rust_i18n::on_locale_change(|event| {
+///     if let rust_i18n::LocaleEvent::LocaleChanged { to, .. } = event {
+///         println!("Locale is changed to {to}");
+///     }
+/// });
+/// ```
+pub fn on_locale_change<F>(observer: F) -> ObserverId
+where
+    F: Fn(&LocaleEvent) + Send + Sync + 'static,
+{
+    let id = ObserverId(NEXT_OBSERVER_ID.fetch_add(1, Ordering::Relaxed));
+    LOCALE_OBSERVERS
+        .write()
+        .unwrap()
+        .push((id, Arc::new(observer)));
+    id
+}
+
+/// Remove an observer added by [`on_locale_change`], return `false` if it was already removed
+pub fn remove_locale_observer(id: ObserverId) -> bool {
+    let mut observers = LOCALE_OBSERVERS.write().unwrap();
+    let len = observers.len();
+    observers.retain(|(observer_id, _)| *observer_id != id);
+    observers.len() != len
+}
+
+/// Notify observers that translations are changed, e.g. after a custom backend reloaded its files
+pub fn notify_reload() {
+    notify(LocaleEvent::Reloaded);
+}
+
+fn notify(event: LocaleEvent) {
+    LOCALE_EPOCH.fetch_add(1, Ordering::AcqRel);
+
+    // Call observers without holding the lock, so they can change the locale or add observers
+    let observers: Vec<Observer> = LOCALE_OBSERVERS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(_, observer)| observer.clone())
+        .collect();
+    for observer in observers {
+        observer(&event);
+    }
+}
+
+/// Backends registered for crates by [`register_backend`], the last registered first
+static REGISTERED_BACKENDS: Lazy<RwLock<HashMap<String, Vec<&'static dyn Backend>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+static HAS_REGISTERED_BACKENDS: AtomicBool = AtomicBool::new(false);
+
+/// Register a backend to override and extend the translations of a crate, which called `i18n!`
+///
+/// Translations of the backend take precedence over the ones of the crate (and of backends registered before),
+/// locales that the crate didn't ship are added. The backend is kept for the rest of the program.
+///
+/// ```ignore
+/// // Translations of the application for the `foo` library, in `locales/foo/`
+/// mod foo_locales {
+///     rust_i18n::i18n!("locales/foo");
+/// }
+///
+/// rust_i18n::register_backend("foo", foo_locales::_rust_i18n_backend());
+/// ```
+pub fn register_backend<B: Backend>(crate_name: &str, backend: B) {
+    let backend: &'static dyn Backend = Box::leak(Box::new(backend));
+    REGISTERED_BACKENDS
+        .write()
+        .unwrap()
+        .entry(crate_name.replace('-', "_"))
+        .or_default()
+        .insert(0, backend);
+    HAS_REGISTERED_BACKENDS.store(true, Ordering::Release);
+
+    notify(LocaleEvent::Reloaded);
+}
+
+/// Get the translation from backends registered for the crate
+#[doc(hidden)]
+pub fn translate_registered(crate_name: &str, locale: &str, key: &str) -> Option<&'static str> {
+    if !HAS_REGISTERED_BACKENDS.load(Ordering::Acquire) {
+        return None;
+    }
+
+    let backends = REGISTERED_BACKENDS.read().unwrap();
+    backends
+        .get(&crate_name.replace('-', "_"))?
+        .iter()
+        .copied()
+        .find_map(|backend| backend.translate(locale, key))
+}
+
+/// Get the locales of backends registered for the crate
+#[doc(hidden)]
+pub fn registered_locales(crate_name: &str) -> Vec<&'static str> {
+    let backends = REGISTERED_BACKENDS.read().unwrap();
+    let Some(backends) = backends.get(&crate_name.replace('-', "_")) else {
+        return vec![];
+    };
+
+    let mut locales = vec![];
+    for &backend in backends {
+        for locale in backend.available_locales() {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
+    }
+    locales
+}
+
+/// Get the metadata of the locale from backends registered for the crate
+#[doc(hidden)]
+pub fn registered_locale_info(crate_name: &str, locale: &str) -> Option<LocaleInfo> {
+    if !HAS_REGISTERED_BACKENDS.load(Ordering::Acquire) {
+        return None;
+    }
+
+    let backends = REGISTERED_BACKENDS.read().unwrap();
+    backends
+        .get(&crate_name.replace('-', "_"))?
+        .iter()
+        .find_map(|backend| backend.locale_info(locale))
+}
+
 /// Get I18n text
 ///
 /// ```ignore
@@ -36,56 +226,183 @@ pub fn locale() -> String {
 ///
 /// // With locale and variables
 /// t!("messages.hello", locale = "de", "Jason"); // messages.hello: "Hallo, {}" => "Hallo, Jason"
+///
+/// // With a variant selected by a variable, `invite: { "%{gender}": { female: "She invited you", other: "They invited you" } }`
+/// t!("invite", gender = "female"); // => "She invited you"
+/// t!("invite", gender = "unknown"); // => "They invited you"
+///
+/// // With context, for the same text with different meanings
+/// t!("Open", context = "menu"); // Open|menu: "Öffnen" => "Öffnen"
+/// t!("Open", locale = "de", context = "state"); // Open|state: "Geöffnet" => "Geöffnet"
+/// t!("Open", context = "state", locale = "de"); // `locale`, `context` and variables in any order
 /// ```
 #[macro_export]
 #[allow(clippy::crate_in_macro_def)]
 macro_rules! t {
-    // t!("foo")
-    ($key:expr) => {
-        crate::_rust_i18n_translate(rust_i18n::locale().as_str(), #[allow(unused_doc_comments)] $key)
+    // Sort the arguments, given in any order, into locale, context and variables
+    (@sort $key:expr; [$($locale:expr)?]; $context:tt; $vars:tt; locale = $new_locale:expr $(, $($rest:tt)*)?) => {
+        $crate::t!(@sort_locale $key; [$($locale)?]; [$new_locale]; $context; $vars; $($($rest)*)?)
+    };
+    (@sort $key:expr; $locale:tt; [$($context:expr)?]; $vars:tt; context = $new_context:expr $(, $($rest:tt)*)?) => {
+        $crate::t!(@sort_context $key; $locale; [$($context)?]; [$new_context]; $vars; $($($rest)*)?)
+    };
+    (@sort $key:expr; $locale:tt; $context:tt; [$($vars:tt)*]; $var_name:tt = $var_val:expr $(, $($rest:tt)*)?) => {
+        $crate::t!(@sort $key; $locale; $context; [$($vars)* $var_name = $var_val,]; $($($rest)*)?)
+    };
+    (@sort $key:expr; $locale:tt; $context:tt; [$($vars:tt)*]; $var_name:tt => $var_val:expr $(, $($rest:tt)*)?) => {
+        $crate::t!(@sort $key; $locale; $context; [$($vars)* $var_name = $var_val,]; $($($rest)*)?)
+    };
+    (@sort $key:expr; []; $context:tt; $vars:tt;) => {
+        $crate::t!(@sorted $key; [&rust_i18n::locale()]; $context; $vars)
+    };
+    (@sort $key:expr; [$locale:expr]; $context:tt; $vars:tt;) => {
+        $crate::t!(@sorted $key; [$locale]; $context; $vars)
+    };
+    (@sort_locale $key:expr; []; [$locale:expr]; $context:tt; $vars:tt; $($rest:tt)*) => {
+        $crate::t!(@sort $key; [$locale]; $context; $vars; $($rest)*)
+    };
+    (@sort_locale $key:expr; [$locale:expr]; $($rest:tt)*) => {
+        compile_error!("`locale` is given more than once")
+    };
+    (@sort_context $key:expr; $locale:tt; []; [$context:expr]; $vars:tt; $($rest:tt)*) => {
+        $crate::t!(@sort $key; $locale; [$context]; $vars; $($rest)*)
+    };
+    (@sort_context $key:expr; $locale:tt; [$context:expr]; $($rest:tt)*) => {
+        compile_error!("`context` is given more than once")
     };
 
     // t!("foo", locale = "en")
-    ($key:expr, locale = $locale:expr) => {
+    (@sorted $key:expr; [$locale:expr]; []; []) => {
         crate::_rust_i18n_translate($locale, #[allow(unused_doc_comments)] $key)
     };
 
+    // t!("foo", locale = "en", context = "menu")
+    (@sorted $key:expr; [$locale:expr]; [$context:expr]; []) => {
+        crate::_rust_i18n_translate_with_context($locale, #[allow(unused_doc_comments)] $key, $context)
+    };
+
+    // t!("foo", locale = "en", context = "menu", a = 1, b = "Foo")
+    (@sorted $key:expr; [$locale:expr]; [$context:expr]; [$($var_name:tt = $var_val:expr,)+]) => {
+        {
+            #[allow(unused_imports)]
+            use rust_i18n::icu::{ToDisplayArgument as _, ToTypedArgument as _};
+
+            let args: &[(&str, rust_i18n::icu::Argument)] = &[$((stringify!($var_name).trim_matches('"'), (&rust_i18n::icu::ArgumentWrap(&$var_val)).to_argument())),+];
+            // Select the variant of the text with the context by the variables
+            let mut message = crate::_rust_i18n_translate_select_with_context($locale, #[allow(unused_doc_comments)] $key, $context, args);
+
+            for (var_name, var_val) in args {
+                let holder = format!("%{{{var_name}}}");
+
+                message = message.replace(&holder, &var_val.to_string());
+            }
+            message
+        }
+    };
+
     // t!("foo", locale = "en", a = 1, b = "Foo")
-    ($key:expr, locale = $locale:expr, $($var_name:tt = $var_val:expr),+ $(,)?) => {
+    (@sorted $key:expr; [$locale:expr]; []; [$($var_name:tt = $var_val:expr,)+]) => {
         {
-            let mut message = crate::_rust_i18n_translate($locale, #[allow(unused_doc_comments)] $key);
+            #[allow(unused_imports)]
+            use rust_i18n::icu::{ToDisplayArgument as _, ToTypedArgument as _};
+
+            // Get the variable names as strings, and remove quotes surrounding the variable names,
+            // numbers and `SystemTime` are passed as typed arguments of ICU MessageFormat
+            let args: &[(&str, rust_i18n::icu::Argument)] = &[$((stringify!($var_name).trim_matches('"'), (&rust_i18n::icu::ArgumentWrap(&$var_val)).to_argument())),+];
+            // Select the variant by the variables, e.g. `%{gender}: { female: ..., other: ... }`
+            let mut message = crate::_rust_i18n_translate_select($locale, #[allow(unused_doc_comments)] $key, args);
 
-            $(
-                // Get the variable name as a string, and remove quotes surrounding the variable name
-                let var_name = stringify!($var_name).trim_matches('"');
+            for (var_name, var_val) in args {
                 // Make a holder string to replace the variable name with: %{var_name}
                 let holder = format!("%{{{var_name}}}");
 
-                message = message.replace(&holder, &format!("{}", $var_val));
-            )+
+                message = message.replace(&holder, &var_val.to_string());
+            }
             message
         }
     };
 
-    // t!("foo %{a} %{b}", a = "bar", b = "baz")
+    // t!("foo")
+    ($key:expr) => {
+        crate::_rust_i18n_translate(rust_i18n::locale().as_str(), #[allow(unused_doc_comments)] $key)
+    };
+
+    // t!("foo", locale = "en", context = "menu", a = 1, "b" => "Foo"), `locale`, `context` and
+    // variables in any order
+    ($key:expr, $($args:tt)+) => {
+        $crate::t!(@sort $key; []; []; []; $($args)+)
+    };
+}
+
+/// Get I18n texts of a list as `Vec<String>`
+///
+/// Lists in locale files are flatten into `key.0`, `key.1`, ..., all items are returned in order.
+///
+/// ```ignore
+/// // days: ["Monday", "Tuesday"]
+/// t_list!("days"); // => vec!["Monday", "Tuesday"]
+/// t_list!("days", locale = "fr"); // => vec!["Lundi", "Mardi"]
+///
+/// // With variables, replaced in every item
+/// t_list!("steps", name = "Jason");
+/// ```
+#[macro_export]
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! t_list {
+    // t_list!("foo")
+    ($key:expr) => {
+        crate::_rust_i18n_translate_list(rust_i18n::locale().as_str(), #[allow(unused_doc_comments)] $key)
+    };
+
+    // t_list!("foo", locale = "en")
+    ($key:expr, locale = $locale:expr) => {
+        crate::_rust_i18n_translate_list($locale, #[allow(unused_doc_comments)] $key)
+    };
+
+    // t_list!("foo", locale = "en", a = 1, b = "Foo")
+    ($key:expr, locale = $locale:expr, $($var_name:tt = $var_val:expr),+ $(,)?) => {
+        crate::_rust_i18n_translate_list($locale, #[allow(unused_doc_comments)] $key)
+            .into_iter()
+            .map(|mut message| {
+                $(
+                    let var_name = stringify!($var_name).trim_matches('"');
+                    let holder = format!("%{{{var_name}}}");
+
+                    message = message.replace(&holder, &format!("{}", $var_val));
+                )+
+                message
+            })
+            .collect::<Vec<String>>()
+    };
+
+    // t_list!("foo", a = "bar", b = "baz")
     ($key:expr, $($var_name:tt = $var_val:expr),+ $(,)?) => {
-        {
-            t!($key, locale = &rust_i18n::locale(), $($var_name = $var_val),*)
-        }
+        $crate::t_list!($key, locale = &rust_i18n::locale(), $($var_name = $var_val),*)
     };
+}
 
-    // t!("foo %{a} %{b}", locale = "en", "a" => "bar", "b" => "baz")
-    ($key:expr, locale = $locale:expr, $($var_name:tt => $var_val:expr),+ $(,)?) => {
-        {
-            t!($key, locale = $locale, $($var_name = $var_val),*)
-        }
+/// Keep keys that are used by `t!` with a non-literal key, so `cargo i18n` does not move them to `REMOVED`
+///
+/// Expands to nothing at runtime, it only marks the keys for the extractor.
+///
+/// ```ignore
+/// // t!(format!("status.{}", status)) somewhere
+/// rust_i18n::keep_keys!("status.active", "status.disabled");
+/// // or keep all keys starting with `status.`
+/// rust_i18n::keep_keys!(prefix = "status.");
+/// ```
+#[macro_export]
+macro_rules! keep_keys {
+    (prefix = $prefix:literal $(,)?) => {
+        const _: () = {
+            let _rust_i18n_keep_prefix = $prefix;
+        };
     };
 
-    // t!("foo %{a} %{b}", "a" => "bar", "b" => "baz")
-    ($key:expr, $($var_name:tt => $var_val:expr),+ $(,)?) => {
-        {
-            t!($key, locale = &rust_i18n::locale(), $($var_name = $var_val),*)
-        }
+    ($($key:literal),+ $(,)?) => {
+        const _: () = {
+            let _rust_i18n_keep_keys = [$($key),+];
+        };
     };
 }
 
@@ -103,6 +420,81 @@ macro_rules! available_locales {
     };
 }
 
+/// Get the default locale from `default-locale` of the I18n config in Cargo.toml, default: "en"
+///
+/// ```ignore
+/// rust_i18n::set_locale(rust_i18n::default_locale!());
+/// ```
+#[macro_export(local_inner_macros)]
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! default_locale {
+    () => {
+        crate::_rust_i18n_default_locale()
+    };
+}
+
+/// Set current locale from the environment (`LANGUAGE`, `LC_ALL`, `LC_MESSAGES`, `LANG`),
+/// negotiated against `available_locales!()`, falling back to `default_locale!()`
+///
+/// Returns the locale that was set.
+///
+/// ```ignore
+/// // LANG=pt_BR.UTF-8, available locales: ["en", "pt"]
+/// rust_i18n::set_system_locale!();
+/// // => "pt"
+/// ```
+#[macro_export(local_inner_macros)]
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! set_system_locale {
+    () => {
+        rust_i18n::set_locale_from_system(
+            &crate::_rust_i18n_available_locales(),
+            crate::_rust_i18n_default_locale(),
+        )
+    };
+}
+
+/// Free memory used by translations of the locale, if `i18n!` was called with `lazy = true`
+///
+/// Translations of the locale are decoded again on its next use, observers are notified
+/// with [`LocaleEvent::Reloaded`]. Decoding a locale on its first use doesn't change texts,
+/// so observers are not notified then.
+///
+/// ```ignore
+/// rust_i18n::unload_locale!("fr");
+/// ```
+#[macro_export(local_inner_macros)]
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! unload_locale {
+    ($locale:expr) => {{
+        crate::_rust_i18n_unload_locale($locale);
+        rust_i18n::notify_reload();
+    }};
+}
+
+/// Get the metadata of a locale (direction, native and English names, plural rules), default: the current locale
+///
+/// Built-in defaults are overridden by the `_meta` section of locale files and by backends.
+///
+/// ```ignore
+/// let info = rust_i18n::locale_info!();
+/// if info.is_rtl() {
+///     // layout from right to left
+/// }
+/// rust_i18n::locale_info!("ar").native_name; // => "العربية"
+/// ```
+#[macro_export(local_inner_macros)]
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! locale_info {
+    () => {
+        crate::_rust_i18n_locale_info(&rust_i18n::locale())
+    };
+
+    ($locale:expr) => {
+        crate::_rust_i18n_locale_info($locale)
+    };
+}
+
 pub trait ToStringI18N {
     fn to_string_i18n(&self) -> String;
 }
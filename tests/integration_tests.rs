@@ -68,6 +68,324 @@ mod tests {
         rust_i18n::i18n!(fallback = "foo");
     }
 
+    mod test_lazy {
+        rust_i18n::i18n!("./tests/locales", fallback = "en", lazy = true);
+
+        #[test]
+        fn test_lazy() {
+            assert_eq!(_rust_i18n_available_locales(), vec!["en", "ja", "zh-CN"]);
+            assert_eq!(_rust_i18n_translate("en", "hello"), "Bar - Hello, World!");
+            assert_eq!(_rust_i18n_translate("zh-CN", "hello"), "Bar - 你好世界！");
+            assert_eq!(
+                _rust_i18n_translate("zh-CN", "missing.default"),
+                "This is missing key fallbacked to en."
+            );
+
+            let epoch = rust_i18n::locale_epoch();
+            rust_i18n::unload_locale!("zh-CN");
+            assert!(rust_i18n::locale_epoch() > epoch);
+            assert_eq!(_rust_i18n_translate("zh-CN", "hello"), "Bar - 你好世界！");
+        }
+    }
+
+    mod test_compact {
+        rust_i18n::i18n!("./tests/locales", fallback = "en", compact = true);
+
+        #[test]
+        fn test_compact() {
+            assert_eq!(_rust_i18n_available_locales(), vec!["en", "ja", "zh-CN"]);
+            assert_eq!(_rust_i18n_translate("en", "hello"), "Bar - Hello, World!");
+            assert_eq!(
+                _rust_i18n_translate("ja", "nested_locale_test"),
+                "こんにちは test"
+            );
+            assert_eq!(
+                _rust_i18n_translate("zh-CN", "missing.default"),
+                "This is missing key fallbacked to en."
+            );
+            assert_eq!(
+                _rust_i18n_translate("en", "nested_locale_test.hello.world"),
+                "Hello test3"
+            );
+        }
+    }
+
+    mod test_canonical_locales {
+        rust_i18n::i18n!(
+            "./tests/canonical",
+            fallback = "en_US",
+            canonicalize_locales = true
+        );
+
+        #[test]
+        fn test_canonical_locales() {
+            assert_eq!(_rust_i18n_available_locales(), vec!["en-US", "he", "zh-CN"]);
+            assert_eq!(_rust_i18n_translate("en-US", "hello"), "Hello");
+            assert_eq!(_rust_i18n_translate("zh-CN", "greeting"), "你好");
+            assert_eq!(_rust_i18n_translate("he", "greeting"), "שלום");
+            assert_eq!(_rust_i18n_translate("zh-CN", "hello"), "Hello");
+            assert_eq!(_rust_i18n_translate("zh_CN", "greeting"), "zh_CN.greeting");
+            // `translations.yml` has no valid locale, it is skipped
+            assert_eq!(_rust_i18n_translate("en-US", "stray"), "en-US.stray");
+        }
+    }
+
+    mod test_review {
+        rust_i18n::i18n!("./tests/review", fallback = "en");
+
+        #[test]
+        fn test_unreviewed_translations() {
+            assert_eq!(_rust_i18n_translate("fr", "hello"), "Bonjour");
+            assert_eq!(_rust_i18n_translate("fr", "save_file"), "Save the file");
+            // Only translations of TODO files are to be reviewed
+            assert_eq!(
+                _rust_i18n_translate("fr", "brand"),
+                "STALE Bread Boulangerie"
+            );
+            assert_eq!(_rust_i18n_translate("en", "brand"), "STALE Bread Bakery");
+        }
+    }
+
+    mod test_icu {
+        use rust_i18n::icu::Argument;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        rust_i18n::i18n!("./tests/icu", fallback = "en", icu = true);
+
+        fn translate(locale: &str, key: &str, args: &[(&str, Argument)]) -> String {
+            _rust_i18n_translate_select(locale, key, args)
+        }
+
+        #[test]
+        fn test_icu() {
+            let files = |count: i64| [("count", count.into()), ("dir", "src".into())];
+            assert_eq!(translate("en", "files", &files(0)), "No files in %{dir}");
+            assert_eq!(translate("en", "files", &files(1)), "1 file in %{dir}");
+            assert_eq!(
+                translate("en", "files", &files(1000)),
+                "1,000 files in %{dir}"
+            );
+            assert_eq!(translate("ru", "files", &files(3)), "3 файла в %{dir}");
+            assert_eq!(translate("ru", "files", &files(25)), "25 файлов в %{dir}");
+            assert_eq!(translate("fr", "files", &files(2)), "2 files in %{dir}");
+            assert_eq!(
+                _rust_i18n_translate_select_with_context("en", "files", "menu", &files(3)),
+                "Open 3 files"
+            );
+            assert_eq!(
+                _rust_i18n_translate_select_with_context("en", "files", "unknown", &files(3)),
+                "3 files in %{dir}"
+            );
+
+            let args = [("gender", "female".into()), ("guests", 3.into())];
+            assert_eq!(translate("en", "invite", &args), "She invited 3 guests.");
+
+            let args = [("total", 1234.5.into())];
+            assert_eq!(translate("en", "price", &args), "Total: 1,234.50");
+            assert_eq!(translate("de", "price", &args), "Summe: 1.234,50");
+
+            let args = [(
+                "date",
+                (UNIX_EPOCH + Duration::from_secs(1699192989)).into(),
+            )];
+            assert_eq!(translate("en", "due", &args), "Due on November 5, 2023");
+
+            assert_eq!(_rust_i18n_translate("en", "quoted"), "It's {literal}");
+        }
+    }
+
+    #[cfg(feature = "compress")]
+    mod test_compress {
+        rust_i18n::i18n!("./tests/locales", fallback = "en", compress = true);
+
+        #[test]
+        fn test_compress() {
+            assert_eq!(_rust_i18n_translate("zh-CN", "hello"), "Bar - 你好世界！");
+            assert_eq!(
+                _rust_i18n_translate("zh-CN", "missing.default"),
+                "This is missing key fallbacked to en."
+            );
+
+            rust_i18n::unload_locale!("zh-CN");
+            assert_eq!(_rust_i18n_translate("zh-CN", "hello"), "Bar - 你好世界！");
+        }
+    }
+
+    mod test_locales {
+        rust_i18n::i18n!("./tests/locales", locales = ["zh-CN"], fallback = "en");
+
+        #[test]
+        fn test_locales() {
+            assert_eq!(_rust_i18n_available_locales(), vec!["en", "zh-CN"]);
+            assert_eq!(_rust_i18n_translate("zh-CN", "hello"), "Bar - 你好世界！");
+            assert_eq!(
+                _rust_i18n_translate("ja", "nested_locale_test"),
+                "Hello test"
+            );
+        }
+    }
+
+    #[allow(unexpected_cfgs)]
+    mod test_locale_features {
+        rust_i18n::i18n!("./tests/locales", fallback = "en", locale_features = true);
+
+        #[test]
+        fn test_locale_features() {
+            assert_eq!(_rust_i18n_available_locales(), vec!["en"]);
+            assert_eq!(
+                _rust_i18n_translate("zh-CN", "hello"),
+                "Bar - Hello, World!"
+            );
+        }
+
+        mod lazy {
+            rust_i18n::i18n!(
+                "./tests/locales",
+                fallback = "en",
+                lazy = true,
+                locale_features = true
+            );
+
+            #[test]
+            fn test_locale_features_lazy() {
+                assert_eq!(_rust_i18n_available_locales(), vec!["en"]);
+            }
+        }
+
+        mod compact {
+            rust_i18n::i18n!(
+                "./tests/locales",
+                fallback = "en",
+                compact = true,
+                locale_features = true
+            );
+
+            #[test]
+            fn test_locale_features_compact() {
+                assert_eq!(_rust_i18n_available_locales(), vec!["en"]);
+                assert_eq!(_rust_i18n_translate("en", "hello"), "Bar - Hello, World!");
+                assert_eq!(
+                    _rust_i18n_translate("zh-CN", "hello"),
+                    "Bar - Hello, World!"
+                );
+            }
+        }
+    }
+
+    mod test_multiple_paths {
+        rust_i18n::i18n!(["./tests/namespaced/locales", "./tests/locales"]);
+
+        #[test]
+        fn test_multiple_paths() {
+            assert_eq!(_rust_i18n_translate("en", "hello"), "Hello");
+            assert_eq!(_rust_i18n_translate("zh-CN", "hello"), "Bar - 你好世界！");
+            assert_eq!(
+                _rust_i18n_translate("en", "messages.hello"),
+                "Hello, %{name}!"
+            );
+            assert_eq!(_rust_i18n_translate("fr", "title"), "Titre");
+        }
+    }
+
+    mod test_register_backend {
+        use rust_i18n::SimpleBackend;
+
+        // Translations of this crate for the `foo` library
+        mod foo_locales {
+            rust_i18n::i18n!("./tests/foo-locales");
+        }
+
+        #[test]
+        fn test_register_backend() {
+            assert_eq!(
+                foo::_rust_i18n_translate("en", "hello"),
+                "Foo - Hello, World!"
+            );
+            assert_eq!(foo::_rust_i18n_available_locales(), vec!["en", "fr"]);
+
+            rust_i18n::register_backend("foo", foo_locales::_rust_i18n_backend());
+            assert_eq!(
+                foo::_rust_i18n_translate("en", "hello"),
+                "App - Hello, Foo!"
+            );
+            assert_eq!(
+                foo::_rust_i18n_translate("fr", "hello"),
+                "Foo - Bonjour, monde!"
+            );
+            assert_eq!(
+                foo::_rust_i18n_translate("de", "hello"),
+                "Foo - Hallo, Welt!"
+            );
+            assert_eq!(
+                foo::_rust_i18n_translate("de", "hello_fallback"),
+                "This hello_fallback is fallback to en."
+            );
+            assert_eq!(foo::_rust_i18n_available_locales(), vec!["de", "en", "fr"]);
+
+            // The last registered backend takes precedence
+            let mut backend = SimpleBackend::new();
+            backend.add_translations("fr", &[("hello", "Salut")].into_iter().collect());
+            rust_i18n::register_backend("foo", backend);
+            assert_eq!(foo::_rust_i18n_translate("fr", "hello"), "Salut");
+            assert_eq!(
+                foo::_rust_i18n_translate("en", "hello"),
+                "App - Hello, Foo!"
+            );
+
+            // Other crates are not affected
+            assert_eq!(
+                crate::_rust_i18n_translate("en", "hello"),
+                "Bar - Hello, World!"
+            );
+        }
+    }
+
+    mod test_namespaced {
+        rust_i18n::i18n!("./tests/namespaced/locales", namespaced = true);
+
+        #[test]
+        fn test_namespaced() {
+            assert_eq!(_rust_i18n_translate("en", "hello"), "Hello");
+            assert_eq!(_rust_i18n_translate("en", "view.title"), "View title");
+            assert_eq!(_rust_i18n_translate("en", "view.buttons.ok"), "Ok");
+            assert_eq!(_rust_i18n_translate("fr", "view.title"), "Titre");
+            assert_eq!(_rust_i18n_translate("en", "admin.users.title"), "Users");
+            assert_eq!(_rust_i18n_translate("en", "title"), "en.title");
+        }
+    }
+
+    mod test_directory_layout {
+        rust_i18n::i18n!("./tests/directory/locales", layout = "directory");
+
+        #[test]
+        fn test_directory_layout() {
+            assert_eq!(_rust_i18n_available_locales(), vec!["en", "fr"]);
+            assert_eq!(_rust_i18n_translate("en", "hello"), "Hello");
+            assert_eq!(_rust_i18n_translate("fr", "hello"), "Bonjour");
+            assert_eq!(_rust_i18n_translate("en", "title"), "Admin");
+            assert_eq!(_rust_i18n_translate("fr", "title"), "Utilisateurs");
+        }
+
+        mod namespaced {
+            rust_i18n::i18n!(
+                "./tests/directory/locales",
+                layout = "directory",
+                namespaced = true
+            );
+
+            #[test]
+            fn test_directory_layout_namespaced() {
+                assert_eq!(_rust_i18n_translate("en", "messages.hello"), "Hello");
+                assert_eq!(_rust_i18n_translate("fr", "messages.hello"), "Bonjour");
+                assert_eq!(_rust_i18n_translate("en", "admin.title"), "Admin");
+                assert_eq!(
+                    _rust_i18n_translate("fr", "admin.users.title"),
+                    "Utilisateurs"
+                );
+            }
+        }
+    }
+
     #[test]
     fn check_test_environment() {
         assert_eq!(
@@ -253,6 +571,232 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_t_list() {
+        rust_i18n::set_locale("en");
+        assert_eq!(
+            rust_i18n::t_list!("days"),
+            vec!["Monday", "Tuesday", "Wednesday"]
+        );
+        assert_eq!(
+            rust_i18n::t_list!("days", locale = "zh-CN"),
+            vec!["星期一", "星期二", "星期三"]
+        );
+        assert_eq!(
+            rust_i18n::t_list!("steps", locale = "zh-CN", name = "Jason"),
+            vec!["Hello, Jason!", "Bye, Jason!"]
+        );
+        assert_eq!(
+            rust_i18n::t_list!("steps", name = "Jason"),
+            vec!["Hello, Jason!", "Bye, Jason!"]
+        );
+        assert_eq!(t!("days.1", locale = "zh-CN"), "星期二");
+        assert!(rust_i18n::t_list!("missing.list").is_empty());
+    }
+
+    rust_i18n::keep_keys!(prefix = "messages.");
+
+    #[test]
+    fn test_keep_keys() {
+        rust_i18n::keep_keys!("hello", "messages.hello");
+
+        let key = format!("messages.{}", "hello");
+        assert_eq!(t!(key.as_str(), locale = "en"), "Hello, %{name}!");
+    }
+
+    #[test]
+    fn test_set_system_locale() {
+        let vars = ["LANGUAGE", "LC_ALL", "LC_MESSAGES", "LANG"];
+        let saved: Vec<_> = vars.iter().map(std::env::var_os).collect();
+        for var in vars {
+            std::env::remove_var(var);
+        }
+
+        std::env::set_var("LANG", "pt_BR.UTF-8@euro");
+        assert_eq!(rust_i18n::set_system_locale!(), "pt");
+        assert_eq!(rust_i18n::locale(), "pt");
+
+        std::env::set_var("LANGUAGE", "fr:zh_CN");
+        assert_eq!(rust_i18n::set_system_locale!(), "zh-CN");
+
+        std::env::set_var("LC_ALL", "C");
+        assert_eq!(rust_i18n::set_system_locale!(), "en");
+
+        for (var, value) in vars.iter().zip(saved) {
+            match value {
+                Some(value) => std::env::set_var(var, value),
+                None => std::env::remove_var(var),
+            }
+        }
+        rust_i18n::set_locale("en");
+    }
+
+    #[test]
+    fn test_locale_observers() {
+        use rust_i18n::LocaleEvent;
+        use std::sync::{Arc, Mutex};
+
+        rust_i18n::set_locale("en");
+        let events = Arc::new(Mutex::new(vec![]));
+        let id = rust_i18n::on_locale_change({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event.clone())
+        });
+
+        let epoch = rust_i18n::locale_epoch();
+        rust_i18n::set_locale("en");
+        assert_eq!(rust_i18n::locale_epoch(), epoch);
+
+        rust_i18n::set_locale("zh-CN");
+        rust_i18n::notify_reload();
+        assert_eq!(rust_i18n::locale_epoch(), epoch + 2);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                LocaleEvent::LocaleChanged {
+                    from: "en".to_string(),
+                    to: "zh-CN".to_string()
+                },
+                LocaleEvent::Reloaded
+            ]
+        );
+
+        assert!(rust_i18n::remove_locale_observer(id));
+        assert!(!rust_i18n::remove_locale_observer(id));
+        rust_i18n::set_locale("en");
+        assert_eq!(events.lock().unwrap().len(), 2);
+        assert_eq!(rust_i18n::locale_epoch(), epoch + 3);
+
+        // Observers can change the locale
+        let id = rust_i18n::on_locale_change(|event| {
+            if let LocaleEvent::LocaleChanged { to, .. } = event {
+                if to == "unknown" {
+                    rust_i18n::set_locale("en");
+                }
+            }
+        });
+        rust_i18n::set_locale("unknown");
+        assert_eq!(rust_i18n::locale(), "en");
+        rust_i18n::remove_locale_observer(id);
+    }
+
+    #[test]
+    fn test_locale_info() {
+        let info = rust_i18n::locale_info!("zh-CN");
+        assert_eq!(info.native_name, "中文（简体）");
+        assert_eq!(info.english_name, "Chinese (Simplified)");
+        assert_eq!(info.direction, rust_i18n::Direction::Ltr);
+        assert!(rust_i18n::locale_info!("ar").is_rtl());
+
+        rust_i18n::set_locale("en");
+        assert_eq!(rust_i18n::locale_info!().native_name, "English");
+
+        // `_meta` is not a translation
+        assert_eq!(
+            t!("_meta.native-name", locale = "zh-CN"),
+            "zh-CN._meta.native-name"
+        );
+    }
+
+    #[test]
+    fn test_t_with_typed_args() {
+        rust_i18n::set_locale("en");
+        assert_eq!(t!("messages.hello", name = 0.1f32), "Hello, 0.1!");
+        assert_eq!(t!("messages.hello", name = 3 + 2), "Hello, 5!");
+        assert_eq!(
+            t!("messages.hello", name = u64::MAX),
+            "Hello, 18446744073709551615!"
+        );
+        assert_eq!(t!("messages.hello", name = 'J'), "Hello, J!");
+    }
+
+    #[test]
+    fn test_t_with_select() {
+        // Only keys with variants are looked up by the arguments
+        assert!(crate::_RUST_I18N_SELECT_KEYS.contains(&"invite"));
+        assert!(!crate::_RUST_I18N_SELECT_KEYS.contains(&"messages.hello"));
+
+        rust_i18n::set_locale("en");
+        assert_eq!(t!("invite", name = "Jason"), "They invited Jason.");
+        assert_eq!(
+            t!("invite", gender = "female", name = "Jason"),
+            "She invited Jason."
+        );
+        assert_eq!(
+            t!("invite", name = "Jason", gender = "male"),
+            "He invited Jason."
+        );
+        assert_eq!(
+            t!("invite", gender = "unknown", name = "Jason"),
+            "They invited Jason."
+        );
+        assert_eq!(
+            t!("invite", locale = "zh-CN", gender = "male", name = "Jason"),
+            "他邀请了Jason。"
+        );
+        assert_eq!(
+            t!("invite", locale = "zh-CN", "gender" => "female", "name" => "Jason"),
+            "TA 邀请了Jason。"
+        );
+        assert_eq!(t!("invite.%{gender}.male"), "He invited %{name}.");
+
+        assert_eq!(
+            t!(
+                "invite",
+                context = "formal",
+                gender = "male",
+                name = "Jason"
+            ),
+            "Mr. Jason was invited."
+        );
+        assert_eq!(
+            t!("invite", name = "Jason", context = "formal"),
+            "Jason was invited."
+        );
+        assert_eq!(
+            t!(
+                "invite",
+                context = "unknown",
+                gender = "female",
+                name = "Jason"
+            ),
+            "She invited Jason."
+        );
+    }
+
+    #[test]
+    fn test_t_with_context() {
+        rust_i18n::set_locale("en");
+        assert_eq!(t!("Open"), "Open");
+        assert_eq!(t!("Open", context = "menu"), "Open…");
+        assert_eq!(t!("Open", context = "state", name = "file"), "Opened file");
+        assert_eq!(t!("Open", locale = "zh-CN", context = "menu"), "打开");
+        assert_eq!(t!("Open", locale = "zh-CN", context = "state"), "已打开");
+        assert_eq!(
+            t!("Open", locale = "zh-CN", context = "state", name = "file"),
+            "已打开"
+        );
+
+        // Arguments in any order
+        assert_eq!(t!("Open", name = "x", context = "state"), "Opened x");
+        assert_eq!(t!("Open", context = "menu", locale = "zh-CN"), "打开");
+        assert_eq!(
+            t!("Open", name = "x", context = "state", locale = "en"),
+            "Opened x"
+        );
+        assert_eq!(
+            t!("Open", context = "menu", "name" => "x", locale = "zh-CN"),
+            "打开"
+        );
+
+        // Fallback to the text without context
+        assert_eq!(t!("Open", context = "unknown"), "Open");
+        assert_eq!(
+            t!("hello", locale = "zh-CN", context = "unknown"),
+            "Bar - 你好世界！"
+        );
+    }
+
     #[test]
     fn test_extend_backend() {
         assert_eq!(t!("foo", locale = "pt"), "pt-fake.foo")